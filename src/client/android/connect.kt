@@ -1,12 +1,19 @@
 package com.example.imported_rust
 
 import android.util.Log
+import whatsapp.ExportFooter
+import whatsapp.ExportHeader
 import whatsapp.WhatsAppExport // Pre-generated using protoc
+import whatsapp.WhatsAppMessage
+import java.io.ByteArrayInputStream
 import java.io.File
 import java.text.SimpleDateFormat
 import java.util.Date
 import java.util.Locale
 
+// Thrown by the native parser; code is the stable ParseError code.
+class WhatsAppParseException(val code: Int, message: String) : Exception(message)
+
 class WhatsAppAndroidConnector {
 
     init {
@@ -16,10 +23,55 @@ class WhatsAppAndroidConnector {
 
     // 2. Call the Rust FFI function (Requires JNI mapping in Rust)
     private external fun parseChatNative(path: String): ByteArray?
+    private external fun parseChatNamedNative(path: String, chatName: String?): ByteArray?
+    private external fun parseChatWithOptionsNative(path: String, options: ByteArray?): ByteArray?
+    private external fun openChatStreamNative(path: String, options: ByteArray?): Long
+    private external fun nextChatChunkNative(handle: Long, chunkSize: Int): ByteArray?
+    private external fun closeChatStreamNative(handle: Long)
+
+    // Streams the export as an ExportHeader followed by one WhatsAppMessage
+    // at a time, so the whole chat never has to be held in memory. An empty
+    // record ends the messages; the ExportFooter after it holds the
+    // diagnostics raised while reading them.
+    fun streamChat(zipPath: String, options: ByteArray? = null, onHeader: (ExportHeader) -> Unit, onMessage: (WhatsAppMessage) -> Unit, onFooter: (ExportFooter) -> Unit) {
+        val handle = openChatStreamNative(zipPath, options)
+        try {
+            var headerRead = false
+            var messagesEnded = false
+            while (true) {
+                val chunk = nextChatChunkNative(handle, 64 * 1024) ?: break
+                // Each chunk holds whole records, so it can be decoded on its own.
+                val input = ByteArrayInputStream(chunk)
+                if (!headerRead) {
+                    onHeader(ExportHeader.parseDelimitedFrom(input))
+                    headerRead = true
+                }
+                while (input.available() > 0) {
+                    if (messagesEnded) {
+                        onFooter(ExportFooter.parseDelimitedFrom(input))
+                        continue
+                    }
+                    val message = WhatsAppMessage.parseDelimitedFrom(input)
+                    if (message.contentCase == WhatsAppMessage.ContentCase.CONTENT_NOT_SET) {
+                        messagesEnded = true
+                    } else {
+                        onMessage(message)
+                    }
+                }
+            }
+        } finally {
+            closeChatStreamNative(handle)
+        }
+    }
 
     fun parseChatAndGetProtoBytes(zipPath: String): ByteArray? {
         Log.d("WhatsAppParser", "Starting Rust Engine for file: $zipPath")
-        val protoBytes = parseChatNative(zipPath)
+        val protoBytes = try {
+            parseChatNative(zipPath)
+        } catch (e: WhatsAppParseException) {
+            Log.e("WhatsAppParser", "Parse failed with code ${e.code}: ${e.message}")
+            null
+        }
         
         if (protoBytes != null) {
             try {
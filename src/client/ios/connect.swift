@@ -41,8 +41,67 @@ class WhatsAppiOSImporter {
             } catch {
                 print("❌ Protobuf Deserialization Failed: \(error)")
             }
+        } else if buffer.error_code != 0 {
+            let message = buffer.error_message.map { String(cString: $0) } ?? ""
+            print("❌ Rust Core failed with code \(buffer.error_code): \(message)")
         } else {
             print("❌ Nothing returned from Rust Core")
         }
     }
+
+    // Streams the export instead, decoding one message at a time so the
+    // whole chat never has to be held in memory.
+    func startStreamingImport(fromPath path: String) {
+        var stream: OpaquePointer? = nil
+        let opened = open_chat_stream_ffi((path as NSString).utf8String, nil, 0, &stream)
+        defer { free_byte_buffer(opened) }
+        guard let stream = stream else {
+            let message = opened.error_message.map { String(cString: $0) } ?? ""
+            print("❌ Rust Core failed with code \(opened.error_code): \(message)")
+            return
+        }
+        defer { close_chat_stream_ffi(stream) }
+
+        var headerRead = false
+        var messagesEnded = false
+        while true {
+            let chunk = next_chat_chunk_ffi(stream, 64 * 1024)
+            defer { free_byte_buffer(chunk) }
+            if chunk.error_code != 0 {
+                let message = chunk.error_message.map { String(cString: $0) } ?? ""
+                print("❌ Rust Core failed with code \(chunk.error_code): \(message)")
+                return
+            }
+            guard let rawData = chunk.data, chunk.len > 0 else { break }
+
+            // Each chunk holds whole records, so it can be decoded on its own.
+            let input = InputStream(data: Data(bytes: rawData, count: Int(chunk.len)))
+            input.open()
+            defer { input.close() }
+            do {
+                if !headerRead {
+                    let header = try BinaryDelimited.parse(messageType: Whatsapp_ExportHeader.self, from: input)
+                    print("✅ Importing Chat: \(header.chatName)")
+                    headerRead = true
+                }
+                while input.hasBytesAvailable {
+                    if messagesEnded {
+                        let footer = try BinaryDelimited.parse(messageType: Whatsapp_ExportFooter.self, from: input)
+                        print("⚠️ \(footer.diagnostics.count) problems found while importing")
+                        continue
+                    }
+                    let message = try BinaryDelimited.parse(messageType: Whatsapp_WhatsAppMessage.self, from: input)
+                    // An empty record ends the messages; the footer follows.
+                    if message.content == nil {
+                        messagesEnded = true
+                    } else if message.hasText {
+                         print("[\(message.text.base.sender)]: \(message.text.text)")
+                    }
+                }
+            } catch {
+                print("❌ Protobuf Deserialization Failed: \(error)")
+                return
+            }
+        }
+    }
 }
@@ -7,10 +7,14 @@
 /**
  * Matching the 'ByteBuffer' struct from interop.rs
  * This tells Swift how to read the data returned by Rust.
+ * On failure data is NULL, error_code holds the ParseError code and
+ * error_message a NUL-terminated description.
  */
 typedef struct {
     uint8_t *data;
     size_t len;
+    uint32_t error_code;
+    char *error_message;
 } ByteBuffer;
 
 /**
@@ -19,6 +23,47 @@ typedef struct {
  */
 ByteBuffer parse_chat_ffi(const char *path);
 
+/**
+ * Same as parse_chat_ffi, but names the chat chat_name; pass NULL to infer it.
+ * Matches: #[unsafe(no_mangle)] pub extern "C" fn parse_chat_named_ffi
+ */
+ByteBuffer parse_chat_named_ffi(const char *path, const char *chat_name);
+
+/**
+ * Same as parse_chat_ffi, configured by a serialized whatsapp.ParseOptions
+ * message of options_len bytes; pass NULL for the defaults.
+ * Matches: #[unsafe(no_mangle)] pub extern "C" fn parse_chat_with_options_ffi
+ */
+ByteBuffer parse_chat_with_options_ffi(const char *path, const uint8_t *options, size_t options_len);
+
+/**
+ * An export being streamed as length-delimited records: one
+ * whatsapp.ExportHeader, then one whatsapp.WhatsAppMessage per message.
+ */
+typedef struct ChatStream ChatStream;
+
+/**
+ * Opens path for streaming, configured as for parse_chat_with_options_ffi.
+ * On success *stream receives a handle and the returned buffer is empty;
+ * on failure *stream is NULL and the buffer carries the error. Free the
+ * buffer either way, and release the handle with close_chat_stream_ffi.
+ * Matches: #[unsafe(no_mangle)] pub extern "C" fn open_chat_stream_ffi
+ */
+ByteBuffer open_chat_stream_ffi(const char *path, const uint8_t *options, size_t options_len, ChatStream **stream);
+
+/**
+ * Returns at least chunk_size bytes of whole records unless the export
+ * ends first; an empty buffer without an error marks the end.
+ * Matches: #[unsafe(no_mangle)] pub extern "C" fn next_chat_chunk_ffi
+ */
+ByteBuffer next_chat_chunk_ffi(ChatStream *stream, size_t chunk_size);
+
+/**
+ * Releases a handle from open_chat_stream_ffi.
+ * Matches: #[unsafe(no_mangle)] pub extern "C" fn close_chat_stream_ffi
+ */
+void close_chat_stream_ffi(ChatStream *stream);
+
 /**
  * Function to free the memory allocated by Rust.
  * Matches: #[unsafe(no_mangle)] pub extern "C" fn free_byte_buffer
@@ -1,4 +1,4 @@
-import init, { parse_chat_wasm } from '../../../pkg/rust_core.js';
+import init, { parse_chat_wasm, parse_chat_with_options_wasm, open_chat_stream_wasm } from '../../../pkg/rust_core.js';
 import protobuf from 'protobufjs';
 
 let wasmReady = false;
@@ -33,4 +33,86 @@ export function decodeWhatsAppExport(bytes) {
     });
 }
 
-export { parse_chat_wasm };
+// Streams an export as a ReadableStream of Uint8Array chunks, each holding
+// whole length-delimited records: one ExportHeader, one WhatsAppMessage per
+// message, an empty record and an ExportFooter. options is a serialized
+// ParseOptions (empty for defaults).
+export function openChatStream(zipBytes, options = new Uint8Array(), chunkSize = 64 * 1024) {
+    const stream = open_chat_stream_wasm(zipBytes, options);
+    return new ReadableStream({
+        pull(controller) {
+            try {
+                const chunk = stream.next_chunk(chunkSize);
+                if (chunk) {
+                    controller.enqueue(chunk);
+                } else {
+                    controller.close();
+                    stream.free();
+                }
+            } catch (failure) {
+                controller.error(failure);
+                stream.free();
+            }
+        },
+        cancel() {
+            stream.free();
+        }
+    }, { highWaterMark: 1 });
+}
+
+// Decodes a stream from openChatStream, yielding { header } first, then
+// { message } for each message as its chunk arrives and { footer } last,
+// with the diagnostics raised while reading the messages. A record cut off
+// at the end of a chunk, as after re-chunking by a transport, is completed
+// by the chunks after it.
+export async function* decodeChatStream(readable) {
+    if (!root) throw new Error("Protobuf not initialized");
+    const ExportHeader = root.lookupType("whatsapp.ExportHeader");
+    const WhatsAppMessage = root.lookupType("whatsapp.WhatsAppMessage");
+    const ExportFooter = root.lookupType("whatsapp.ExportFooter");
+    const toObject = (type, value) => type.toObject(value, {
+        longs: String,
+        enums: String,
+        bytes: String,
+        defaults: true,
+        arrays: true,
+        objects: true,
+        oneofs: true
+    });
+    let headerRead = false;
+    let messagesEnded = false;
+    let pending = new Uint8Array(0);
+    for await (const chunk of readable) {
+        const buffer = new Uint8Array(pending.length + chunk.length);
+        buffer.set(pending);
+        buffer.set(chunk, pending.length);
+        const reader = protobuf.Reader.create(buffer);
+        let consumed = 0;
+        while (reader.pos < reader.len) {
+            let length;
+            try {
+                length = reader.uint32();
+            } catch (e) {
+                break; // The length prefix itself is cut off.
+            }
+            if (reader.pos + length > reader.len) break;
+            const record = buffer.subarray(reader.pos, reader.pos + length);
+            reader.skip(length);
+            consumed = reader.pos;
+            if (!headerRead) {
+                yield { header: toObject(ExportHeader, ExportHeader.decode(record)) };
+                headerRead = true;
+            } else if (messagesEnded) {
+                yield { footer: toObject(ExportFooter, ExportFooter.decode(record)) };
+            } else if (length === 0) {
+                messagesEnded = true; // No message is empty; the footer follows.
+            } else {
+                yield { message: toObject(WhatsAppMessage, WhatsAppMessage.decode(record)) };
+            }
+        }
+        pending = buffer.slice(consumed);
+    }
+    if (pending.length > 0) throw new Error("Chat stream ended inside a record");
+}
+
+export { parse_chat_wasm, parse_chat_with_options_wasm };
@@ -1,3 +1,13 @@
+pub mod date_format;
+pub mod delimited_export;
+pub mod group_roster;
+pub mod locale;
 pub mod messages;
+pub mod parse_error;
+pub mod parse_options;
+pub mod system_event;
+pub mod text_encoding;
+pub mod time_zone;
+pub mod vcard;
 pub mod whats_app_export;
 pub mod whats_app_parse;
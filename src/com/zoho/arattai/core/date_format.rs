@@ -0,0 +1,309 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use regex::{Captures, Regex};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+/// A header timestamp split into its numeric fields, e.g.
+/// `12.03.24, 9:01 pm` or `2024-03-12 21:01:33`.
+static TIMESTAMP: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)^(\d{1,4})([./-])(\d{1,2})[./-](\d{1,4}),?\s+(\d{1,2}):(\d{2})(?::(\d{2}))?(?:\s*([ap])\.?\s?m\.?)?$",
+    )
+    .expect("valid timestamp pattern")
+});
+
+/// The order in which day, month and year appear in a transcript date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DateOrder {
+    DayMonthYear,
+    MonthDayYear,
+    YearMonthDay,
+}
+
+/// The date and clock layout used by the headers of one transcript.
+///
+/// WhatsApp writes timestamps in the phone's regional format, so the same
+/// export feature produces `12/03/2024, 9:01 pm`, `3/12/24, 21:01` or
+/// `12.03.24, 21:01` depending on the device. A `DateFormat` is normally
+/// inferred by [`DateFormat::detect`], but it can also be written as a
+/// pattern string such as `dd/mm/yyyy, h:mm a` and parsed back with
+/// [`str::parse`] so callers can override the detection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DateFormat {
+    /// The position of the day, month and year fields.
+    pub order: DateOrder,
+    /// The character between the date fields: `/`, `.` or `-`.
+    pub separator: char,
+    /// Whether the year is written with four digits rather than two.
+    pub four_digit_year: bool,
+    /// Whether the clock is 12-hour with an AM/PM marker.
+    pub twelve_hour: bool,
+}
+
+impl Default for DateFormat {
+    /// The classic Android layout: `dd/mm/yyyy, h:mm a`.
+    fn default() -> Self {
+        Self {
+            order: DateOrder::DayMonthYear,
+            separator: '/',
+            four_digit_year: true,
+            twelve_hour: true,
+        }
+    }
+}
+
+impl DateFormat {
+    /// Infers the format from a sample of raw header timestamps.
+    ///
+    /// Any first field above 12 marks the file as day-first and any second
+    /// field above 12 as month-first. When no such evidence exists the
+    /// ordering that keeps the messages chronological wins, falling back to
+    /// day-first. Returns `None` if none of the samples look like a timestamp.
+    pub fn detect<'a, I>(timestamps: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut detector = DateFormatDetector::default();
+        for timestamp in timestamps {
+            detector.add(timestamp);
+        }
+        detector.finish()
+    }
+
+    /// Parses one raw header timestamp. Seconds are optional, and a 12-hour
+    /// marker is honoured even if the format was detected as 24-hour.
+    pub fn parse(&self, raw: &str) -> Option<NaiveDateTime> {
+        let clean = Self::clean(raw);
+        let caps = TIMESTAMP.captures(&clean)?;
+        self.read_captures(&caps)
+    }
+
+    fn read_captures(&self, caps: &Captures) -> Option<NaiveDateTime> {
+        let first: u32 = caps[1].parse().ok()?;
+        let second: u32 = caps[3].parse().ok()?;
+        let third: u32 = caps[4].parse().ok()?;
+        let (year, month, day) = match self.order {
+            DateOrder::DayMonthYear => (third, second, first),
+            DateOrder::MonthDayYear => (third, first, second),
+            DateOrder::YearMonthDay => (first, second, third),
+        };
+        let year = if year < 100 { 2000 + year } else { year };
+
+        let mut hour: u32 = caps[5].parse().ok()?;
+        let minute: u32 = caps[6].parse().ok()?;
+        let second: u32 = caps.get(7).map_or(Some(0), |s| s.as_str().parse().ok())?;
+        if let Some(marker) = caps.get(8) {
+            // Japanese clocks run from 午前0 to 午後11, so hour 0 is
+            // midnight or noon just as 12 is on an English clock.
+            if hour > 12 {
+                return None;
+            }
+            let pm = marker.as_str().eq_ignore_ascii_case("p");
+            hour = match (hour, pm) {
+                (0 | 12, false) => 0,
+                (0 | 12, true) => 12,
+                (h, true) => h + 12,
+                (h, false) => h,
+            };
+        }
+
+        NaiveDate::from_ymd_opt(year as i32, month, day)?.and_hms_opt(hour, minute, second)
+    }
+
+    /// Collapses runs of whitespace, including the narrow no-break space
+    /// newer Android builds put before the AM/PM marker.
+    fn clean(raw: &str) -> String {
+        raw.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+}
+
+/// Infers a [`DateFormat`] from timestamps fed to it one at a time, so
+/// that a long transcript need not be sampled into memory first.
+/// [`DateFormat::detect`] is the same over a whole sample.
+#[derive(Debug, Clone, Default)]
+pub struct DateFormatDetector {
+    separator: Option<char>,
+    year_first: bool,
+    four_digit_year: bool,
+    twelve_hour: bool,
+    /// How many first and second fields were above 12.
+    above_twelve: [usize; 2],
+    /// The last time read day-first and month-first, and how many times
+    /// each reading went back in time.
+    previous: [Option<NaiveDateTime>; 2],
+    backward_steps: [usize; 2],
+}
+
+impl DateFormatDetector {
+    const ORDERS: [DateOrder; 2] = [DateOrder::DayMonthYear, DateOrder::MonthDayYear];
+
+    /// Takes one raw header timestamp into account; anything that does not
+    /// look like a timestamp is ignored.
+    pub fn add(&mut self, raw: &str) {
+        let clean = DateFormat::clean(raw);
+        let Some(caps) = TIMESTAMP.captures(&clean) else { return };
+        self.separator.get_or_insert_with(|| caps[2].chars().next().unwrap_or('/'));
+        self.year_first |= caps[1].len() == 4;
+        self.four_digit_year |= caps[4].len() == 4;
+        self.twelve_hour |= caps.get(8).is_some();
+        for (count, field) in self.above_twelve.iter_mut().zip([1, 3]) {
+            if caps[field].parse::<u32>().is_ok_and(|v| v > 12) {
+                *count += 1;
+            }
+        }
+        for (i, order) in Self::ORDERS.into_iter().enumerate() {
+            let format = DateFormat { order, ..DateFormat::default() };
+            let Some(current) = format.read_captures(&caps) else { continue };
+            if self.previous[i].is_some_and(|p| current < p) {
+                self.backward_steps[i] += 1;
+            }
+            self.previous[i] = Some(current);
+        }
+    }
+
+    /// Returns the format the timestamps seen so far point to, or `None` if
+    /// none of them looked like a timestamp.
+    pub fn finish(self) -> Option<DateFormat> {
+        let separator = self.separator?;
+        let order = if self.year_first {
+            DateOrder::YearMonthDay
+        } else {
+            let [day_first, month_first] = self.above_twelve;
+            if day_first > month_first {
+                DateOrder::DayMonthYear
+            } else if month_first > day_first {
+                DateOrder::MonthDayYear
+            } else {
+                let [dmy, mdy] = self.backward_steps;
+                if mdy < dmy { DateOrder::MonthDayYear } else { DateOrder::DayMonthYear }
+            }
+        };
+        Some(DateFormat {
+            order,
+            separator,
+            four_digit_year: self.year_first || self.four_digit_year,
+            twelve_hour: self.twelve_hour,
+        })
+    }
+}
+
+impl fmt::Display for DateFormat {
+    /// Writes the format as a pattern such as `dd/mm/yyyy, h:mm a` or
+    /// `mm.dd.yy, HH:mm`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let year = if self.four_digit_year { "yyyy" } else { "yy" };
+        let fields = match self.order {
+            DateOrder::DayMonthYear => ["dd", "mm", year],
+            DateOrder::MonthDayYear => ["mm", "dd", year],
+            DateOrder::YearMonthDay => [year, "mm", "dd"],
+        };
+        let sep = self.separator.to_string();
+        let clock = if self.twelve_hour { "h:mm a" } else { "HH:mm" };
+        write!(f, "{}, {}", fields.join(&sep), clock)
+    }
+}
+
+impl FromStr for DateFormat {
+    type Err = String;
+
+    /// Reads a pattern in the form produced by the `Display` implementation.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("unrecognised date format \"{}\"", s);
+        let (date, clock) = s.split_once(',').ok_or_else(invalid)?;
+        let separator = date
+            .trim()
+            .chars()
+            .find(|c| matches!(c, '/' | '.' | '-'))
+            .ok_or_else(invalid)?;
+        let fields: Vec<String> = date.trim().split(separator).map(|f| f.to_lowercase()).collect();
+        let order = match fields.iter().filter_map(|f| f.chars().next()).collect::<String>().as_str() {
+            "dmy" => DateOrder::DayMonthYear,
+            "mdy" => DateOrder::MonthDayYear,
+            "ymd" => DateOrder::YearMonthDay,
+            _ => return Err(invalid()),
+        };
+        let four_digit_year = fields.iter().any(|f| f == "yyyy");
+        let twelve_hour = clock.trim().ends_with('a');
+        Ok(Self { order, separator, four_digit_year, twelve_hour })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(format: &DateFormat, raw: &str) -> Option<String> {
+        format.parse(raw).map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+    }
+
+    #[test]
+    fn reads_spaced_meridiem_markers() {
+        let format = DateFormat::default();
+        assert_eq!(at(&format, "12/03/2024, 9:01 a. m.").as_deref(), Some("2024-03-12 09:01"));
+        assert_eq!(at(&format, "12/03/2024, 9:01 p. m.").as_deref(), Some("2024-03-12 21:01"));
+        assert_eq!(at(&format, "12/03/2024, 12:15\u{202f}a.m.").as_deref(), Some("2024-03-12 00:15"));
+        assert_eq!(at(&format, "12/03/2024, 12:15 PM").as_deref(), Some("2024-03-12 12:15"));
+    }
+
+    #[test]
+    fn reads_hour_zero_with_a_marker() {
+        let format = DateFormat { order: DateOrder::YearMonthDay, ..DateFormat::default() };
+        assert_eq!(at(&format, "2024/03/12 0:30 am").as_deref(), Some("2024-03-12 00:30"));
+        assert_eq!(at(&format, "2024/03/12 0:30 pm").as_deref(), Some("2024-03-12 12:30"));
+        assert_eq!(at(&format, "2024/03/12 13:30 pm"), None);
+    }
+
+    fn detect(samples: &[&str]) -> DateFormat {
+        DateFormat::detect(samples.iter().copied()).unwrap()
+    }
+
+    #[test]
+    fn a_field_above_twelve_decides_the_order() {
+        let day_first = detect(&["03/04/2024, 9:00 am", "25/04/2024, 9:00 am"]);
+        assert_eq!(day_first.order, DateOrder::DayMonthYear);
+        let month_first = detect(&["04/03/2024, 9:00 am", "04/25/2024, 9:00 am"]);
+        assert_eq!(month_first.order, DateOrder::MonthDayYear);
+        assert_eq!(month_first.to_string(), "mm/dd/yyyy, h:mm a");
+    }
+
+    #[test]
+    fn going_back_in_time_breaks_a_tie() {
+        // Read day-first these go from 1 February back to 3 January.
+        let month_first = detect(&["01/02/24, 10:00", "03/01/24, 10:00", "05/01/24, 10:00"]);
+        assert_eq!(month_first.order, DateOrder::MonthDayYear);
+        let day_first = detect(&["02/01/24, 10:00", "01/03/24, 10:00", "01/05/24, 10:00"]);
+        assert_eq!(day_first.order, DateOrder::DayMonthYear);
+        // With no evidence either way, day-first wins.
+        assert_eq!(detect(&["01/01/24, 10:00"]).order, DateOrder::DayMonthYear);
+    }
+
+    #[test]
+    fn detects_year_first_and_24_hour_clocks() {
+        let format = detect(&["2024-03-12 21:01", "2024-03-13 09:15"]);
+        let expected = DateFormat { order: DateOrder::YearMonthDay, separator: '-', four_digit_year: true, twelve_hour: false };
+        assert_eq!(format, expected);
+        assert_eq!(at(&format, "2024-03-12 21:01").as_deref(), Some("2024-03-12 21:01"));
+        assert_eq!(DateFormat::detect(["hello", ""]), None);
+    }
+
+    #[test]
+    fn display_and_from_str_round_trip() {
+        for order in [DateOrder::DayMonthYear, DateOrder::MonthDayYear, DateOrder::YearMonthDay] {
+            for separator in ['/', '.', '-'] {
+                for (four_digit_year, twelve_hour) in [(true, true), (false, false)] {
+                    let format = DateFormat { order, separator, four_digit_year, twelve_hour };
+                    assert_eq!(format.to_string().parse::<DateFormat>(), Ok(format), "{}", format);
+                }
+            }
+        }
+        assert_eq!("dd.mm.yy, HH:mm".parse::<DateFormat>().unwrap().separator, '.');
+    }
+
+    #[test]
+    fn rejects_bad_format_strings() {
+        for bad in ["", "dd/mm/yyyy", "dd mm yyyy, HH:mm", "dd/yy/mm, HH:mm", "hh/mm/ss, HH:mm"] {
+            assert!(bad.parse::<DateFormat>().is_err(), "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use crate::com::zoho::arattai::core::messages::{ExportFooter, ExportHeader};
+use crate::com::zoho::arattai::core::parse_error::ParseError;
+use crate::com::zoho::arattai::core::whats_app_parse::MessageStream;
+use prost::Message;
+
+/// An export written as a sequence of length-delimited Protobuf records
+/// instead of one `WhatsAppExport`, so that a client can decode it one
+/// message at a time.
+///
+/// The first record is an `ExportHeader`; every record after it is one
+/// `WhatsAppMessage`, in transcript order, up to an empty record. An
+/// `ExportFooter` with the diagnostics raised while reading the messages
+/// comes last. Each record is prefixed with its length as a varint, the
+/// framing read by `parseDelimitedFrom` in Java, `decodeDelimited` in
+/// protobuf.js and `BinaryDelimited` in Swift. The roster is not written,
+/// as it needs every message; clients that want it can rebuild it from the
+/// system events.
+///
+/// Nothing follows an error, not even the footer.
+pub struct DelimitedExport<'a> {
+    messages: MessageStream<'a>,
+    header_written: bool,
+    /// How many diagnostics the header carried.
+    header_diagnostics: usize,
+    messages_ended: bool,
+    footer_written: bool,
+}
+
+impl<'a> DelimitedExport<'a> {
+    pub fn new(messages: MessageStream<'a>) -> Self {
+        Self { messages, header_written: false, header_diagnostics: 0, messages_ended: false, footer_written: false }
+    }
+
+    /// Returns whole records, at least `chunk_size` bytes of them unless
+    /// the export ends first. An empty chunk marks the end.
+    pub fn next_chunk(&mut self, chunk_size: usize) -> Result<Vec<u8>, ParseError> {
+        let mut chunk = Vec::new();
+        while chunk.len() < chunk_size.max(1) {
+            match self.next() {
+                Some(record) => chunk.extend_from_slice(&record?),
+                None => break,
+            }
+        }
+        Ok(chunk)
+    }
+
+    fn header(&self) -> ExportHeader {
+        ExportHeader {
+            chat_name: self.messages.get_chat_name().to_string(),
+            date_format: self.messages.get_date_format().to_string(),
+            time_zone: self.messages.get_time_zone().to_string(),
+            locale: self.messages.get_locale().code.clone(),
+            diagnostics: self.messages.get_diagnostics().to_vec(),
+        }
+    }
+
+    fn footer(&self) -> ExportFooter {
+        ExportFooter { diagnostics: self.messages.get_diagnostics()[self.header_diagnostics..].to_vec() }
+    }
+}
+
+/// Yields one record at a time, length prefix included.
+impl Iterator for DelimitedExport<'_> {
+    type Item = Result<Vec<u8>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.header_written {
+            self.header_written = true;
+            let header = self.header();
+            self.header_diagnostics = header.diagnostics.len();
+            return Some(Ok(header.encode_length_delimited_to_vec()));
+        }
+        if !self.messages_ended {
+            return match self.messages.next() {
+                Some(Ok(message)) => Some(Ok(message.encode_length_delimited_to_vec())),
+                Some(Err(error)) => {
+                    self.messages_ended = true;
+                    self.footer_written = true;
+                    Some(Err(error))
+                }
+                None => {
+                    self.messages_ended = true;
+                    // A zero length: no message encodes to nothing.
+                    Some(Ok(vec![0]))
+                }
+            };
+        }
+        if !self.footer_written {
+            self.footer_written = true;
+            return Some(Ok(self.footer().encode_length_delimited_to_vec()));
+        }
+        None
+    }
+}
@@ -0,0 +1,200 @@
+use crate::com::zoho::arattai::core::messages::{
+    whatsapp_message, MembershipPeriod, SystemEventKind, WhatsAppMessage,
+};
+use chrono::{DateTime, Utc};
+use prost_types::Timestamp;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Group membership over time, reconstructed from the join and leave
+/// events in a transcript.
+///
+/// Events only cover the period the export spans, so anyone who sends a
+/// message or takes part in an event without a recorded join is assumed to
+/// have been a member since before the export started.
+#[derive(Debug, Clone, Default)]
+pub struct GroupRoster {
+    periods: Vec<MembershipPeriod>,
+}
+
+impl GroupRoster {
+    /// Walks the messages in transcript order and builds one
+    /// `MembershipPeriod` per continuous stretch of membership.
+    pub fn reconstruct(messages: &[WhatsAppMessage]) -> Self {
+        let mut builder = RosterBuilder::default();
+        for msg in messages {
+            let Some(base) = msg.base() else { continue };
+            let at = base.timestamp.as_ref();
+            let Some(whatsapp_message::Content::System(system)) = msg.content.as_ref() else {
+                builder.ensure_member(&base.sender, at);
+                continue;
+            };
+            for actor in system.actors.iter().filter(|a| !system.subjects.contains(a)) {
+                builder.ensure_member(actor, at);
+            }
+            match SystemEventKind::try_from(system.kind) {
+                Ok(SystemEventKind::GroupCreated | SystemEventKind::ParticipantAdded | SystemEventKind::JoinedViaLink) => {
+                    for subject in &system.subjects {
+                        builder.join(subject, at);
+                    }
+                }
+                Ok(SystemEventKind::ParticipantLeft | SystemEventKind::ParticipantRemoved) => {
+                    for subject in &system.subjects {
+                        builder.leave(subject, at);
+                    }
+                }
+                _ => {
+                    for subject in &system.subjects {
+                        builder.ensure_member(subject, at);
+                    }
+                }
+            }
+        }
+        Self { periods: builder.periods }
+    }
+
+    /// Returns every membership period in the order it started.
+    pub fn get_periods(&self) -> &[MembershipPeriod] {
+        &self.periods
+    }
+
+    /// Returns the participants who were in the group at `at`, sorted by name.
+    pub fn members_at(&self, at: DateTime<Utc>) -> Vec<&str> {
+        let at = (at.timestamp(), at.timestamp_subsec_nanos() as i32);
+        let key = |t: &Timestamp| (t.seconds, t.nanos);
+        self.periods
+            .iter()
+            .filter(|p| p.joined.as_ref().is_none_or(|j| key(j) <= at))
+            .filter(|p| p.left.as_ref().is_none_or(|l| key(l) > at))
+            .map(|p| p.participant.as_str())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+}
+
+#[derive(Default)]
+struct RosterBuilder {
+    periods: Vec<MembershipPeriod>,
+    /// Index into `periods` of each participant's current open period.
+    open: HashMap<String, usize>,
+    seen: HashSet<String>,
+}
+
+impl RosterBuilder {
+    fn join(&mut self, name: &str, at: Option<&Timestamp>) {
+        if !self.open.contains_key(name) {
+            self.start(name, at.cloned());
+        }
+    }
+
+    fn leave(&mut self, name: &str, at: Option<&Timestamp>) {
+        if let Some(index) = self.open.remove(name) {
+            self.periods[index].left = at.cloned();
+        } else if !self.seen.contains(name) {
+            self.start(name, None);
+            if let Some(index) = self.open.remove(name) {
+                self.periods[index].left = at.cloned();
+            }
+        }
+    }
+
+    /// Records that `name` was a member at `at` without a join event:
+    /// since the start of the export if they have not been seen before,
+    /// otherwise from `at` after an unrecorded rejoin.
+    fn ensure_member(&mut self, name: &str, at: Option<&Timestamp>) {
+        if name.is_empty() || self.open.contains_key(name) {
+            return;
+        }
+        let joined = if self.seen.contains(name) { at.cloned() } else { None };
+        self.start(name, joined);
+    }
+
+    fn start(&mut self, name: &str, joined: Option<Timestamp>) {
+        self.open.insert(name.to_string(), self.periods.len());
+        self.seen.insert(name.to_string());
+        self.periods.push(MembershipPeriod {
+            participant: name.to_string(),
+            joined,
+            left: None,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::com::zoho::arattai::core::messages::{MessageBase, SystemMessage, TextMessage};
+
+    fn base(sender: &str, seconds: i64) -> Option<MessageBase> {
+        Some(MessageBase {
+            sender: sender.to_string(),
+            timestamp: Some(Timestamp { seconds, nanos: 0 }),
+            ..Default::default()
+        })
+    }
+
+    fn system(kind: SystemEventKind, actors: &[&str], subjects: &[&str], seconds: i64) -> WhatsAppMessage {
+        WhatsAppMessage {
+            content: Some(whatsapp_message::Content::System(SystemMessage {
+                base: base("", seconds),
+                kind: kind as i32,
+                actors: actors.iter().map(|a| a.to_string()).collect(),
+                subjects: subjects.iter().map(|s| s.to_string()).collect(),
+                ..Default::default()
+            })),
+        }
+    }
+
+    fn text(sender: &str, seconds: i64) -> WhatsAppMessage {
+        WhatsAppMessage {
+            content: Some(whatsapp_message::Content::Text(TextMessage { base: base(sender, seconds), text: "hi".to_string() })),
+        }
+    }
+
+    fn period(participant: &str, joined: Option<i64>, left: Option<i64>) -> MembershipPeriod {
+        let at = |seconds| Timestamp { seconds, nanos: 0 };
+        MembershipPeriod { participant: participant.to_string(), joined: joined.map(at), left: left.map(at) }
+    }
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(seconds, 0).unwrap()
+    }
+
+    #[test]
+    fn join_leave_and_rejoin_make_two_periods() {
+        let roster = GroupRoster::reconstruct(&[
+            system(SystemEventKind::ParticipantAdded, &["Alice"], &["Bob"], 100),
+            text("Bob", 200),
+            system(SystemEventKind::ParticipantLeft, &["Bob"], &["Bob"], 300),
+            system(SystemEventKind::ParticipantAdded, &["Alice"], &["Bob"], 400),
+        ]);
+        assert_eq!(
+            roster.get_periods(),
+            [period("Alice", None, None), period("Bob", Some(100), Some(300)), period("Bob", Some(400), None)]
+        );
+        assert_eq!(roster.members_at(at(350)), ["Alice"]);
+        assert_eq!(roster.members_at(at(400)), ["Alice", "Bob"]);
+    }
+
+    #[test]
+    fn leave_without_a_join_starts_before_the_export() {
+        let roster = GroupRoster::reconstruct(&[
+            text("Alice", 100),
+            system(SystemEventKind::ParticipantLeft, &["Carol"], &["Carol"], 200),
+        ]);
+        assert_eq!(roster.get_periods(), [period("Alice", None, None), period("Carol", None, Some(200))]);
+        assert_eq!(roster.members_at(at(0)), ["Alice", "Carol"]);
+    }
+
+    #[test]
+    fn members_at_includes_the_join_and_excludes_the_leave_instant() {
+        let roster = GroupRoster::reconstruct(&[
+            system(SystemEventKind::ParticipantAdded, &["Alice"], &["Bob"], 100),
+            system(SystemEventKind::ParticipantRemoved, &["Alice"], &["Bob"], 300),
+        ]);
+        assert_eq!(roster.members_at(at(99)), ["Alice"]);
+        assert_eq!(roster.members_at(at(100)), ["Alice", "Bob"]);
+        assert_eq!(roster.members_at(at(299)), ["Alice", "Bob"]);
+        assert_eq!(roster.members_at(at(300)), ["Alice"]);
+    }
+}
@@ -0,0 +1,1036 @@
+use crate::com::zoho::arattai::core::messages::{SystemEventKind, Type};
+use crate::com::zoho::arattai::core::system_event::SystemEvent;
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// How many messages `Locale::detect` samples before deciding.
+pub const DETECTION_SAMPLE: usize = 2000;
+
+/// A call log phrase and what it says about the call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallPhrase {
+    pub phrase: String,
+    pub video: bool,
+    pub missed: bool,
+    pub group: bool,
+}
+
+/// A system event phrase. The pattern may capture an `actor`, `subjects`
+/// and a `target`; when `actor_is_subject` is set the actor is also the
+/// subject, as in "Bob left".
+#[derive(Debug, Clone)]
+pub struct SystemPhrase {
+    pub kind: SystemEventKind,
+    pub pattern: Regex,
+    pub actor_is_subject: bool,
+}
+
+/// The fixed phrases WhatsApp writes into a transcript in one display
+/// language.
+///
+/// Every phrase is stored in lowercase. The built-in tables cover the most
+/// common WhatsApp languages and also carry the English phrases, since
+/// some markers are left untranslated. A custom table can be built by
+/// filling in the fields, usually starting from `Locale::english()`.
+#[derive(Debug, Clone)]
+pub struct Locale {
+    /// The language code, such as `en` or `pt`.
+    pub code: String,
+    /// The suffix after an attachment's file name, such as `(file attached)`.
+    pub file_attached: Vec<String>,
+    /// Placeholders for attachments left out of the export, with the kind
+    /// of media each stands for.
+    pub media_omitted: Vec<(String, Type)>,
+    /// Tombstones left in place of a deleted message, without trailing
+    /// punctuation.
+    pub deleted: Vec<String>,
+    /// Suffixes appended to an edited message.
+    pub edited: Vec<String>,
+    /// Prefixes of the line carrying a shared pin.
+    pub location_prefixes: Vec<String>,
+    /// Lines written for a live location share.
+    pub live_location: Vec<String>,
+    /// The line that opens a poll.
+    pub poll_prefixes: Vec<String>,
+    /// The prefix of each poll option line.
+    pub poll_option_prefixes: Vec<String>,
+    /// Lines marking a poll that accepts more than one answer.
+    pub poll_multi_select: Vec<String>,
+    /// Call log phrases, longest first.
+    pub calls: Vec<CallPhrase>,
+    /// Separators in a list of participants, such as `, ` and ` and `.
+    pub list_separators: Vec<String>,
+    /// System event phrases, most specific first.
+    pub system_events: Vec<SystemPhrase>,
+    /// Patterns for the name WhatsApp gives an exported transcript or
+    /// archive, without its extension, capturing the chat's `name`.
+    pub chat_names: Vec<Regex>,
+}
+
+/// The phrases of one built-in language, as written in the source.
+struct Table {
+    code: &'static str,
+    file_attached: &'static [&'static str],
+    media_omitted: &'static [(&'static str, Type)],
+    deleted: &'static [&'static str],
+    edited: &'static [&'static str],
+    location_prefixes: &'static [&'static str],
+    live_location: &'static [&'static str],
+    poll_prefixes: &'static [&'static str],
+    poll_option_prefixes: &'static [&'static str],
+    poll_multi_select: &'static [&'static str],
+    /// Phrase, video, missed, group.
+    calls: &'static [(&'static str, bool, bool, bool)],
+    list_separators: &'static [&'static str],
+    system_events: &'static [(SystemEventKind, &'static str, bool)],
+    chat_names: &'static [&'static str],
+}
+
+const EMPTY: Table = Table {
+    code: "",
+    file_attached: &[],
+    media_omitted: &[],
+    deleted: &[],
+    edited: &[],
+    location_prefixes: &[],
+    live_location: &[],
+    poll_prefixes: &[],
+    poll_option_prefixes: &[],
+    poll_multi_select: &[],
+    calls: &[],
+    list_separators: &[", "],
+    system_events: &[],
+    chat_names: &[],
+};
+
+const ENGLISH: Table = Table {
+    code: "en",
+    file_attached: &["(file attached)"],
+    media_omitted: &[
+        ("<media omitted>", Type::MediaOmitted),
+        ("image omitted", Type::Image),
+        ("video omitted", Type::Video),
+        ("gif omitted", Type::Video),
+        ("sticker omitted", Type::Sticker),
+        ("audio omitted", Type::Audio),
+        ("document omitted", Type::Document),
+        ("contact card omitted", Type::Contact),
+    ],
+    deleted: &["this message was deleted", "you deleted this message"],
+    edited: &["<this message was edited>"],
+    location_prefixes: &["location:"],
+    live_location: &["live location shared"],
+    poll_prefixes: &["poll:"],
+    poll_option_prefixes: &["option:"],
+    poll_multi_select: &["allow multiple answers", "multiple answers allowed"],
+    calls: &[
+        ("missed group voice call", false, true, true),
+        ("missed group video call", true, true, true),
+        ("missed voice call", false, true, false),
+        ("missed video call", true, true, false),
+        ("silenced voice call", false, true, false),
+        ("silenced video call", true, true, false),
+        ("group voice call", false, false, true),
+        ("group video call", true, false, true),
+        ("voice call", false, false, false),
+        ("video call", true, false, false),
+    ],
+    list_separators: &[", ", " and "],
+    chat_names: &[r"^whatsapp chat with (?P<name>.+)$", r"^whatsapp chat - (?P<name>.+)$"],
+    system_events: &[
+        (SystemEventKind::EncryptionNotice, r"^messages and calls are end-to-end encrypted", false),
+        (SystemEventKind::SecurityCodeChanged, r"^your security code with (?P<target>.+?) changed", false),
+        (SystemEventKind::GroupCreated, r#"^(?P<actor>.+?) created group ["“](?P<target>.+)["”]$"#, true),
+        (SystemEventKind::SubjectChanged, r#"^(?P<actor>.+?) changed the (?:subject|group name) (?:from ["“].*["”] )?to ["“](?P<target>.+)["”]$"#, false),
+        (SystemEventKind::IconChanged, r"^(?P<actor>.+?) (?:changed|deleted) this group's icon$", false),
+        (SystemEventKind::DescriptionChanged, r"^(?P<actor>.+?) (?:changed|deleted) the group description", false),
+        (SystemEventKind::SettingsChanged, r"^(?P<actor>.+?) changed (?:this group's |the group's |the group |the )?settings(?: (?:to|so) (?P<target>.+?))?\.?$", false),
+        (SystemEventKind::JoinedViaLink, r"^(?P<actor>.+?) joined using (?:this group's|your) invite link$", true),
+        (SystemEventKind::ParticipantLeft, r"^(?P<actor>.+?) left$", true),
+        (SystemEventKind::AdminGranted, r"^(?P<subjects>you)[’']re now an admin$", false),
+        (SystemEventKind::AdminGranted, r"^(?P<subjects>.+?) (?:is|are) now (?:an )?admins?$", false),
+        (SystemEventKind::AdminRevoked, r"^(?P<subjects>you)[’']re no longer an admin$", false),
+        (SystemEventKind::AdminRevoked, r"^(?P<actor>.+?) dismissed (?P<subjects>.+?) as admins?$", false),
+        (SystemEventKind::ParticipantRemoved, r"^(?P<actor>.+?) removed (?P<subjects>.+)$", false),
+        (SystemEventKind::ParticipantAdded, r"^(?P<actor>.+?) added (?P<subjects>.+)$", false),
+    ],
+};
+
+const GERMAN: Table = Table {
+    code: "de",
+    file_attached: &["(datei angehängt)"],
+    media_omitted: &[
+        ("<medien ausgeschlossen>", Type::MediaOmitted),
+        ("bild weggelassen", Type::Image),
+        ("video weggelassen", Type::Video),
+        ("gif weggelassen", Type::Video),
+        ("sticker weggelassen", Type::Sticker),
+        ("audio weggelassen", Type::Audio),
+        ("dokument weggelassen", Type::Document),
+    ],
+    deleted: &["diese nachricht wurde gelöscht", "du hast diese nachricht gelöscht"],
+    edited: &["<diese nachricht wurde bearbeitet>"],
+    location_prefixes: &["standort:"],
+    live_location: &["live-standort wird geteilt"],
+    poll_prefixes: &["umfrage:"],
+    poll_option_prefixes: &["option:"],
+    poll_multi_select: &["mehrere antworten erlauben"],
+    calls: &[
+        ("verpasster sprachanruf", false, true, false),
+        ("verpasster videoanruf", true, true, false),
+        ("sprachanruf", false, false, false),
+        ("videoanruf", true, false, false),
+    ],
+    list_separators: &[", ", " und "],
+    chat_names: &[r"^whatsapp[- ]chat mit (?P<name>.+)$"],
+    system_events: &[
+        (SystemEventKind::EncryptionNotice, r"^nachrichten und anrufe sind ende-zu-ende-verschlüsselt", false),
+        (SystemEventKind::GroupCreated, r#"^(?P<actor>.+?) hat die gruppe ["„“](?P<target>.+)["“”] erstellt$"#, true),
+        (SystemEventKind::JoinedViaLink, r"^(?P<actor>.+?) ist über den einladungslink dieser gruppe beigetreten$", true),
+        (SystemEventKind::ParticipantLeft, r"^(?P<actor>.+?) hat die gruppe verlassen$", true),
+        (SystemEventKind::ParticipantRemoved, r"^(?P<actor>.+?) hat (?P<subjects>.+) entfernt$", false),
+        (SystemEventKind::ParticipantAdded, r"^(?P<actor>.+?) hat (?P<subjects>.+) hinzugefügt$", false),
+    ],
+};
+
+const SPANISH: Table = Table {
+    code: "es",
+    file_attached: &["(archivo adjunto)"],
+    media_omitted: &[
+        ("<multimedia omitido>", Type::MediaOmitted),
+        ("imagen omitida", Type::Image),
+        ("video omitido", Type::Video),
+        ("gif omitido", Type::Video),
+        ("sticker omitido", Type::Sticker),
+        ("audio omitido", Type::Audio),
+        ("documento omitido", Type::Document),
+    ],
+    deleted: &["se eliminó este mensaje", "eliminaste este mensaje"],
+    edited: &["<se editó este mensaje.>"],
+    location_prefixes: &["ubicación:"],
+    live_location: &["ubicación en tiempo real compartida"],
+    poll_prefixes: &["encuesta:"],
+    poll_option_prefixes: &["opción:"],
+    poll_multi_select: &["permitir varias respuestas"],
+    calls: &[
+        ("llamada de voz perdida", false, true, false),
+        ("videollamada perdida", true, true, false),
+        ("llamada de voz", false, false, false),
+        ("videollamada", true, false, false),
+    ],
+    list_separators: &[", ", " y "],
+    chat_names: &[r"^chat de whatsapp con (?P<name>.+)$"],
+    system_events: &[
+        (SystemEventKind::EncryptionNotice, r"^los mensajes y las llamadas están cifrados de extremo a extremo", false),
+        (SystemEventKind::GroupCreated, r#"^(?P<actor>.+?) creó el grupo ["“](?P<target>.+)["”]$"#, true),
+        (SystemEventKind::JoinedViaLink, r"^(?P<actor>.+?) se unió usando el enlace de invitación de este grupo$", true),
+        (SystemEventKind::ParticipantLeft, r"^(?P<actor>.+?) salió del grupo$", true),
+        (SystemEventKind::ParticipantRemoved, r"^(?P<actor>.+?) eliminó a (?P<subjects>.+)$", false),
+        (SystemEventKind::ParticipantAdded, r"^(?P<actor>.+?) añadió a (?P<subjects>.+)$", false),
+    ],
+};
+
+const PORTUGUESE: Table = Table {
+    code: "pt",
+    file_attached: &["(arquivo anexado)"],
+    media_omitted: &[
+        ("<mídia oculta>", Type::MediaOmitted),
+        ("imagem ocultada", Type::Image),
+        ("vídeo omitido", Type::Video),
+        ("gif omitido", Type::Video),
+        ("figurinha omitida", Type::Sticker),
+        ("áudio ocultado", Type::Audio),
+        ("documento omitido", Type::Document),
+    ],
+    deleted: &["mensagem apagada", "esta mensagem foi apagada", "você apagou esta mensagem"],
+    edited: &["<mensagem editada>"],
+    location_prefixes: &["localização:"],
+    live_location: &["localização em tempo real compartilhada"],
+    poll_prefixes: &["enquete:"],
+    poll_option_prefixes: &["opção:"],
+    poll_multi_select: &["permitir várias respostas"],
+    calls: &[
+        ("chamada de voz perdida", false, true, false),
+        ("chamada de vídeo perdida", true, true, false),
+        ("chamada de voz", false, false, false),
+        ("chamada de vídeo", true, false, false),
+    ],
+    list_separators: &[", ", " e "],
+    chat_names: &[r"^conversa do whatsapp com (?P<name>.+)$"],
+    system_events: &[
+        (SystemEventKind::EncryptionNotice, r"^as mensagens e as chamadas são protegidas com a criptografia de ponta a ponta", false),
+        (SystemEventKind::GroupCreated, r#"^(?P<actor>.+?) criou o grupo ["“](?P<target>.+)["”]$"#, true),
+        (SystemEventKind::JoinedViaLink, r"^(?P<actor>.+?) entrou usando o link de convite deste grupo$", true),
+        (SystemEventKind::ParticipantLeft, r"^(?P<actor>.+?) saiu$", true),
+        (SystemEventKind::ParticipantRemoved, r"^(?P<actor>.+?) removeu (?P<subjects>.+)$", false),
+        (SystemEventKind::ParticipantAdded, r"^(?P<actor>.+?) adicionou (?P<subjects>.+)$", false),
+    ],
+};
+
+const FRENCH: Table = Table {
+    code: "fr",
+    file_attached: &["(fichier joint)"],
+    media_omitted: &[
+        ("<médias omis>", Type::MediaOmitted),
+        ("image absente", Type::Image),
+        ("vidéo absente", Type::Video),
+        ("gif omis", Type::Video),
+        ("autocollant omis", Type::Sticker),
+        ("audio omis", Type::Audio),
+        ("document omis", Type::Document),
+    ],
+    deleted: &["ce message a été supprimé", "vous avez supprimé ce message"],
+    edited: &["<ce message a été modifié>"],
+    location_prefixes: &["position :", "position:"],
+    live_location: &["position en direct partagée"],
+    poll_prefixes: &["sondage :", "sondage:"],
+    poll_option_prefixes: &["option :", "option:"],
+    poll_multi_select: &["autoriser plusieurs réponses"],
+    calls: &[
+        ("appel vocal manqué", false, true, false),
+        ("appel vidéo manqué", true, true, false),
+        ("appel vocal", false, false, false),
+        ("appel vidéo", true, false, false),
+    ],
+    list_separators: &[", ", " et "],
+    chat_names: &[r"^discussion whatsapp avec (?P<name>.+)$"],
+    system_events: &[
+        (SystemEventKind::EncryptionNotice, r"^les messages et les appels sont chiffrés de bout en bout", false),
+        (SystemEventKind::GroupCreated, r#"^(?P<actor>.+?) a créé le groupe [«"“]\s*(?P<target>.+?)\s*[»"”]$"#, true),
+        (SystemEventKind::JoinedViaLink, r"^(?P<actor>.+?) a rejoint ce groupe via le lien d['’]invitation$", true),
+        (SystemEventKind::ParticipantLeft, r"^(?P<actor>.+?) est parti(?:e|\(e\))?$", true),
+        (SystemEventKind::ParticipantRemoved, r"^(?P<actor>.+?) a retiré (?P<subjects>.+)$", false),
+        (SystemEventKind::ParticipantAdded, r"^(?P<actor>.+?) a ajouté (?P<subjects>.+)$", false),
+    ],
+};
+
+const ITALIAN: Table = Table {
+    code: "it",
+    file_attached: &["(file allegato)"],
+    media_omitted: &[
+        ("<media omessi>", Type::MediaOmitted),
+        ("immagine omessa", Type::Image),
+        ("video omesso", Type::Video),
+        ("gif omessa", Type::Video),
+        ("sticker omesso", Type::Sticker),
+        ("audio omesso", Type::Audio),
+        ("documento omesso", Type::Document),
+    ],
+    deleted: &["questo messaggio è stato eliminato", "hai eliminato questo messaggio"],
+    edited: &["<questo messaggio è stato modificato>"],
+    location_prefixes: &["posizione:"],
+    live_location: &["posizione in tempo reale condivisa"],
+    poll_prefixes: &["sondaggio:"],
+    poll_option_prefixes: &["opzione:"],
+    poll_multi_select: &["consenti più risposte"],
+    calls: &[
+        ("chiamata vocale persa", false, true, false),
+        ("videochiamata persa", true, true, false),
+        ("chiamata vocale", false, false, false),
+        ("videochiamata", true, false, false),
+    ],
+    list_separators: &[", ", " e "],
+    chat_names: &[r"^chat whatsapp con (?P<name>.+)$"],
+    system_events: &[
+        (SystemEventKind::EncryptionNotice, r"^i messaggi e le chiamate sono crittografati end-to-end", false),
+        (SystemEventKind::GroupCreated, r#"^(?P<actor>.+?) ha creato il gruppo ["“](?P<target>.+)["”]$"#, true),
+        (SystemEventKind::JoinedViaLink, r"^(?P<actor>.+?) si è unit[oa] tramite il link d['’]invito al gruppo$", true),
+        (SystemEventKind::ParticipantLeft, r"^(?P<actor>.+?) è uscit[oa]$", true),
+        (SystemEventKind::ParticipantRemoved, r"^(?P<actor>.+?) ha rimosso (?P<subjects>.+)$", false),
+        (SystemEventKind::ParticipantAdded, r"^(?P<actor>.+?) ha aggiunto (?P<subjects>.+)$", false),
+    ],
+};
+
+const DUTCH: Table = Table {
+    code: "nl",
+    file_attached: &["(bestand bijgevoegd)"],
+    media_omitted: &[
+        ("<media weggelaten>", Type::MediaOmitted),
+        ("afbeelding weggelaten", Type::Image),
+        ("video weggelaten", Type::Video),
+        ("gif weggelaten", Type::Video),
+        ("sticker weggelaten", Type::Sticker),
+        ("audio weggelaten", Type::Audio),
+        ("document weggelaten", Type::Document),
+    ],
+    deleted: &["dit bericht is verwijderd", "je hebt dit bericht verwijderd"],
+    edited: &["<dit bericht is bewerkt>"],
+    location_prefixes: &["locatie:"],
+    live_location: &["live locatie gedeeld"],
+    poll_prefixes: &["peiling:"],
+    poll_option_prefixes: &["optie:"],
+    poll_multi_select: &["meerdere antwoorden toestaan"],
+    calls: &[
+        ("gemiste spraakoproep", false, true, false),
+        ("gemiste video-oproep", true, true, false),
+        ("spraakoproep", false, false, false),
+        ("video-oproep", true, false, false),
+    ],
+    list_separators: &[", ", " en "],
+    chat_names: &[r"^whatsapp[- ]chat met (?P<name>.+)$"],
+    system_events: &[
+        (SystemEventKind::EncryptionNotice, r"^berichten en oproepen zijn end-to-end versleuteld", false),
+        (SystemEventKind::GroupCreated, r#"^(?P<actor>.+?) heeft groep ["“](?P<target>.+)["”] aangemaakt$"#, true),
+        (SystemEventKind::ParticipantLeft, r"^(?P<actor>.+?) (?:heeft de groep verlaten|is vertrokken)$", true),
+        (SystemEventKind::ParticipantRemoved, r"^(?P<actor>.+?) heeft (?P<subjects>.+) verwijderd$", false),
+        (SystemEventKind::ParticipantAdded, r"^(?P<actor>.+?) heeft (?P<subjects>.+) toegevoegd$", false),
+    ],
+};
+
+const POLISH: Table = Table {
+    code: "pl",
+    file_attached: &["(plik załączony)"],
+    media_omitted: &[("<pominięto multimedia>", Type::MediaOmitted)],
+    deleted: &["ta wiadomość została usunięta", "usunięto tę wiadomość"],
+    edited: &["<ta wiadomość została edytowana>"],
+    location_prefixes: &["lokalizacja:"],
+    live_location: &[],
+    poll_prefixes: &["ankieta:"],
+    poll_option_prefixes: &["opcja:"],
+    poll_multi_select: &[],
+    calls: &[
+        ("nieodebrane połączenie głosowe", false, true, false),
+        ("nieodebrane połączenie wideo", true, true, false),
+        ("połączenie głosowe", false, false, false),
+        ("połączenie wideo", true, false, false),
+    ],
+    list_separators: &[", ", " i "],
+    chat_names: &[r"^czat whatsapp z (?P<name>.+)$"],
+    system_events: &[
+        (SystemEventKind::EncryptionNotice, r"^wiadomości i połączenia są szyfrowane end-to-end", false),
+        (SystemEventKind::GroupCreated, r#"^(?P<actor>.+?) utworzył(?:a|\(a\))? grupę ["„“](?P<target>.+)["”]$"#, true),
+        (SystemEventKind::ParticipantLeft, r"^(?P<actor>.+?) opuścił(?:a|\(a\))? grupę$", true),
+        (SystemEventKind::ParticipantRemoved, r"^(?P<actor>.+?) usun(?:ął|ęła|ął\(a\)) (?P<subjects>.+)$", false),
+        (SystemEventKind::ParticipantAdded, r"^(?P<actor>.+?) dodał(?:a|\(a\))? (?P<subjects>.+)$", false),
+    ],
+};
+
+const TURKISH: Table = Table {
+    code: "tr",
+    file_attached: &["(dosya ekli)"],
+    media_omitted: &[
+        ("<medya dahil edilmedi>", Type::MediaOmitted),
+        ("görüntü dahil edilmedi", Type::Image),
+        ("video dahil edilmedi", Type::Video),
+        ("gif dahil edilmedi", Type::Video),
+        ("çıkartma dahil edilmedi", Type::Sticker),
+        ("ses dahil edilmedi", Type::Audio),
+        ("belge dahil edilmedi", Type::Document),
+    ],
+    deleted: &["bu mesaj silindi", "bu mesajı sildiniz"],
+    edited: &["<bu mesaj düzenlendi>"],
+    location_prefixes: &["konum:"],
+    live_location: &["canlı konum paylaşıldı"],
+    poll_prefixes: &["anket:"],
+    poll_option_prefixes: &["seçenek:"],
+    poll_multi_select: &[],
+    calls: &[
+        ("cevapsız sesli arama", false, true, false),
+        ("cevapsız görüntülü arama", true, true, false),
+        ("sesli arama", false, false, false),
+        ("görüntülü arama", true, false, false),
+    ],
+    list_separators: &[", ", " ve "],
+    chat_names: &[r"^(?P<name>.+) ile whatsapp sohbeti$"],
+    system_events: &[
+        (SystemEventKind::EncryptionNotice, r"^mesajlar ve aramalar uçtan uca şifrelidir", false),
+        (SystemEventKind::GroupCreated, r#"^(?P<actor>.+?) ["“](?P<target>.+)["”] grubunu oluşturdu$"#, true),
+        (SystemEventKind::ParticipantLeft, r"^(?P<actor>.+?) ayrıldı$", true),
+        (SystemEventKind::ParticipantRemoved, r"^(?P<actor>.+?), (?P<subjects>.+?) kişisini çıkardı$", false),
+        (SystemEventKind::ParticipantAdded, r"^(?P<actor>.+?), (?P<subjects>.+?) kişisini ekledi$", false),
+    ],
+};
+
+const RUSSIAN: Table = Table {
+    code: "ru",
+    file_attached: &["(файл добавлен)"],
+    media_omitted: &[("<без медиафайлов>", Type::MediaOmitted)],
+    deleted: &["данное сообщение удалено", "это сообщение удалено", "вы удалили данное сообщение"],
+    edited: &["<сообщение изменено>"],
+    location_prefixes: &["геопозиция:"],
+    live_location: &[],
+    poll_prefixes: &["опрос:"],
+    poll_option_prefixes: &["вариант:"],
+    poll_multi_select: &[],
+    calls: &[
+        ("пропущенный аудиозвонок", false, true, false),
+        ("пропущенный видеозвонок", true, true, false),
+        ("аудиозвонок", false, false, false),
+        ("видеозвонок", true, false, false),
+    ],
+    list_separators: &[", ", " и "],
+    chat_names: &[r"^чат whatsapp с (?P<name>.+)$"],
+    system_events: &[
+        (SystemEventKind::EncryptionNotice, r"^сообщения и звонки защищены сквозным шифрованием", false),
+        (SystemEventKind::GroupCreated, r#"^(?P<actor>.+?) создал\S* группу [«"“](?P<target>.+)[»"”]$"#, true),
+        (SystemEventKind::ParticipantLeft, r"^(?P<actor>.+?) вы(?:шел|шла)\S*$", true),
+        (SystemEventKind::ParticipantRemoved, r"^(?P<actor>.+?) удалил\S* (?P<subjects>.+)$", false),
+        (SystemEventKind::ParticipantAdded, r"^(?P<actor>.+?) добавил\S* (?P<subjects>.+)$", false),
+    ],
+};
+
+const ARABIC: Table = Table {
+    code: "ar",
+    file_attached: &["(الملف مرفق)"],
+    media_omitted: &[("<تم استبعاد الوسائط>", Type::MediaOmitted)],
+    deleted: &["تم حذف هذه الرسالة", "لقد حذفت هذه الرسالة"],
+    edited: &["<تم تعديل هذه الرسالة>"],
+    location_prefixes: &["الموقع:"],
+    live_location: &[],
+    poll_prefixes: &["استطلاع:"],
+    poll_option_prefixes: &["خيار:"],
+    poll_multi_select: &[],
+    calls: &[
+        ("مكالمة صوتية فائتة", false, true, false),
+        ("مكالمة فيديو فائتة", true, true, false),
+        ("مكالمة صوتية", false, false, false),
+        ("مكالمة فيديو", true, false, false),
+    ],
+    list_separators: &["، ", ", ", " و"],
+    chat_names: &[r"^دردشة واتساب مع (?P<name>.+)$"],
+    system_events: &[
+        (SystemEventKind::GroupCreated, r#"^(?P<actor>.+?) أنشأ\S* المجموعة ["“«](?P<target>.+)["”»]$"#, true),
+        (SystemEventKind::ParticipantLeft, r"^(?P<actor>.+?) غادر\S*$", true),
+        (SystemEventKind::ParticipantRemoved, r"^(?P<actor>.+?) أزال\S* (?P<subjects>.+)$", false),
+        (SystemEventKind::ParticipantAdded, r"^(?P<actor>.+?) أضاف\S* (?P<subjects>.+)$", false),
+    ],
+};
+
+const PERSIAN: Table = Table {
+    code: "fa",
+    file_attached: &["(فایل پیوست شد)"],
+    media_omitted: &[("<رسانه حذف شد>", Type::MediaOmitted)],
+    deleted: &["این پیام حذف شد", "شما این پیام را حذف کردید"],
+    edited: &["<این پیام ویرایش شد>"],
+    calls: &[
+        ("تماس صوتی بی‌پاسخ", false, true, false),
+        ("تماس تصویری بی‌پاسخ", true, true, false),
+        ("تماس صوتی", false, false, false),
+        ("تماس تصویری", true, false, false),
+    ],
+    list_separators: &["، ", ", ", " و "],
+    system_events: &[
+        (SystemEventKind::GroupCreated, r#"^(?P<actor>.+?) گروه ["“«](?P<target>.+)["”»] را ایجاد کرد$"#, true),
+        (SystemEventKind::ParticipantLeft, r"^(?P<actor>.+?) خارج شد$", true),
+        (SystemEventKind::ParticipantRemoved, r"^(?P<actor>.+?) (?P<subjects>.+?) را حذف کرد$", false),
+        (SystemEventKind::ParticipantAdded, r"^(?P<actor>.+?) (?P<subjects>.+?) را اضافه کرد$", false),
+    ],
+    ..EMPTY
+};
+
+const HINDI: Table = Table {
+    code: "hi",
+    file_attached: &["(फ़ाइल अटैच की गई)", "(फ़ाइल संलग्न है)"],
+    media_omitted: &[("<मीडिया छोड़ दिया गया>", Type::MediaOmitted)],
+    deleted: &["यह मैसेज हटा दिया गया था", "आपने यह मैसेज हटा दिया", "आपने यह मैसेज हटाया"],
+    edited: &["<यह मैसेज एडिट किया गया था>"],
+    location_prefixes: &["लोकेशन:"],
+    poll_prefixes: &["पोल:"],
+    poll_option_prefixes: &["विकल्प:"],
+    calls: &[
+        ("मिस्ड वॉइस कॉल", false, true, false),
+        ("मिस्ड वीडियो कॉल", true, true, false),
+        ("वॉइस कॉल", false, false, false),
+        ("वीडियो कॉल", true, false, false),
+    ],
+    list_separators: &[", ", " और "],
+    system_events: &[
+        (SystemEventKind::GroupCreated, r#"^(?P<actor>.+?) ने ग्रुप ["“](?P<target>.+)["”] बनाया$"#, true),
+        (SystemEventKind::ParticipantLeft, r"^(?P<actor>.+?) (?:ने ग्रुप छोड़ दिया|ने छोड़ दिया|चले गए|चली गईं)$", true),
+        (SystemEventKind::ParticipantRemoved, r"^(?P<actor>.+?) ने (?P<subjects>.+?) को हटाया$", false),
+        (SystemEventKind::ParticipantAdded, r"^(?P<actor>.+?) ने (?P<subjects>.+?) को जोड़ा$", false),
+    ],
+    ..EMPTY
+};
+
+const BENGALI: Table = Table {
+    code: "bn",
+    file_attached: &["(ফাইল সংযুক্ত)"],
+    media_omitted: &[("<মিডিয়া বাদ দেওয়া হয়েছে>", Type::MediaOmitted)],
+    deleted: &["এই মেসেজটি মুছে ফেলা হয়েছে", "আপনি এই মেসেজটি মুছে ফেলেছেন"],
+    edited: &["<এই মেসেজটি এডিট করা হয়েছে>"],
+    calls: &[
+        ("মিসড ভয়েস কল", false, true, false),
+        ("মিসড ভিডিও কল", true, true, false),
+        ("ভয়েস কল", false, false, false),
+        ("ভিডিও কল", true, false, false),
+    ],
+    list_separators: &[", ", " ও ", " এবং "],
+    system_events: &[
+        (SystemEventKind::ParticipantLeft, r"^(?P<actor>.+?) চলে গেছেন$", true),
+        (SystemEventKind::ParticipantRemoved, r"^(?P<actor>.+?) (?P<subjects>.+?)-কে সরিয়ে দিয়েছেন$", false),
+        (SystemEventKind::ParticipantAdded, r"^(?P<actor>.+?) (?P<subjects>.+?)-কে যোগ করেছেন$", false),
+    ],
+    ..EMPTY
+};
+
+const TAMIL: Table = Table {
+    code: "ta",
+    file_attached: &["(கோப்பு இணைக்கப்பட்டது)"],
+    media_omitted: &[("<மீடியா தவிர்க்கப்பட்டது>", Type::MediaOmitted)],
+    deleted: &["இந்த மெசேஜ் நீக்கப்பட்டது", "இந்த மெசேஜை நீக்கினீர்கள்"],
+    edited: &["<இந்த மெசேஜ் திருத்தப்பட்டது>"],
+    calls: &[
+        ("தவறிய குரல் அழைப்பு", false, true, false),
+        ("தவறிய வீடியோ அழைப்பு", true, true, false),
+        ("குரல் அழைப்பு", false, false, false),
+        ("வீடியோ அழைப்பு", true, false, false),
+    ],
+    list_separators: &[", ", " மற்றும் "],
+    system_events: &[
+        (SystemEventKind::ParticipantLeft, r"^(?P<actor>.+?) வெளியேறினார்$", true),
+        (SystemEventKind::ParticipantRemoved, r"^(?P<actor>.+?) (?P<subjects>.+?) ஐ அகற்றினார்$", false),
+        (SystemEventKind::ParticipantAdded, r"^(?P<actor>.+?) (?P<subjects>.+?) ஐச் சேர்த்தார்$", false),
+    ],
+    ..EMPTY
+};
+
+const TELUGU: Table = Table {
+    code: "te",
+    file_attached: &["(ఫైల్ జోడించబడింది)"],
+    media_omitted: &[("<మీడియా విస్మరించబడింది>", Type::MediaOmitted)],
+    deleted: &["ఈ మెసేజ్ తొలగించబడింది", "మీరు ఈ మెసేజ్‌ను తొలగించారు"],
+    edited: &["<ఈ మెసేజ్ ఎడిట్ చేయబడింది>"],
+    calls: &[
+        ("మిస్డ్ వాయిస్ కాల్", false, true, false),
+        ("మిస్డ్ వీడియో కాల్", true, true, false),
+        ("వాయిస్ కాల్", false, false, false),
+        ("వీడియో కాల్", true, false, false),
+    ],
+    list_separators: &[", ", " మరియు "],
+    system_events: &[
+        (SystemEventKind::ParticipantLeft, r"^(?P<actor>.+?) నిష్క్రమించారు$", true),
+        (SystemEventKind::ParticipantRemoved, r"^(?P<actor>.+?) (?P<subjects>.+?)ను తీసివేశారు$", false),
+        (SystemEventKind::ParticipantAdded, r"^(?P<actor>.+?) (?P<subjects>.+?)ను జోడించారు$", false),
+    ],
+    ..EMPTY
+};
+
+const MARATHI: Table = Table {
+    code: "mr",
+    file_attached: &["(फाईल संलग्न)"],
+    media_omitted: &[("<मीडिया वगळले>", Type::MediaOmitted)],
+    deleted: &["हा मेसेज हटवला गेला", "तुम्ही हा मेसेज हटवला"],
+    edited: &["<हा मेसेज संपादित केला गेला>"],
+    calls: &[
+        ("मिस्ड व्हॉइस कॉल", false, true, false),
+        ("मिस्ड व्हिडिओ कॉल", true, true, false),
+        ("व्हॉइस कॉल", false, false, false),
+        ("व्हिडिओ कॉल", true, false, false),
+    ],
+    list_separators: &[", ", " आणि "],
+    system_events: &[
+        (SystemEventKind::ParticipantLeft, r"^(?P<actor>.+?) बाहेर पडले$", true),
+        (SystemEventKind::ParticipantRemoved, r"^(?P<actor>.+?) यांनी (?P<subjects>.+?) यांना काढून टाकले$", false),
+        (SystemEventKind::ParticipantAdded, r"^(?P<actor>.+?) यांनी (?P<subjects>.+?) यांना जोडले$", false),
+    ],
+    ..EMPTY
+};
+
+const URDU: Table = Table {
+    code: "ur",
+    file_attached: &["(فائل منسلک ہے)"],
+    media_omitted: &[("<میڈیا خارج کر دیا گیا>", Type::MediaOmitted)],
+    deleted: &["یہ پیغام حذف کر دیا گیا", "آپ نے یہ پیغام حذف کر دیا"],
+    edited: &["<یہ پیغام ترمیم شدہ ہے>"],
+    calls: &[
+        ("مسڈ صوتی کال", false, true, false),
+        ("مسڈ ویڈیو کال", true, true, false),
+        ("صوتی کال", false, false, false),
+        ("ویڈیو کال", true, false, false),
+    ],
+    list_separators: &["، ", ", ", " اور "],
+    system_events: &[
+        (SystemEventKind::ParticipantLeft, r"^(?P<actor>.+?) (?:نے گروپ چھوڑ دیا|چلے گئے|چلی گئیں)$", true),
+        (SystemEventKind::ParticipantRemoved, r"^(?P<actor>.+?) نے (?P<subjects>.+?) کو ہٹا دیا$", false),
+        (SystemEventKind::ParticipantAdded, r"^(?P<actor>.+?) نے (?P<subjects>.+?) کو شامل کیا$", false),
+    ],
+    ..EMPTY
+};
+
+const INDONESIAN: Table = Table {
+    code: "id",
+    file_attached: &["(file terlampir)"],
+    media_omitted: &[
+        ("<media tidak disertakan>", Type::MediaOmitted),
+        ("gambar tidak disertakan", Type::Image),
+        ("video tidak disertakan", Type::Video),
+        ("gif tidak disertakan", Type::Video),
+        ("stiker tidak disertakan", Type::Sticker),
+        ("audio tidak disertakan", Type::Audio),
+        ("dokumen tidak disertakan", Type::Document),
+    ],
+    deleted: &["pesan ini telah dihapus", "anda menghapus pesan ini"],
+    edited: &["<pesan ini diedit>"],
+    location_prefixes: &["lokasi:"],
+    live_location: &["lokasi terkini dibagikan"],
+    poll_prefixes: &["polling:"],
+    poll_option_prefixes: &["opsi:"],
+    poll_multi_select: &["izinkan beberapa jawaban"],
+    calls: &[
+        ("panggilan suara tak terjawab", false, true, false),
+        ("panggilan video tak terjawab", true, true, false),
+        ("panggilan suara", false, false, false),
+        ("panggilan video", true, false, false),
+    ],
+    list_separators: &[", ", " dan "],
+    chat_names: &[r"^chat whatsapp dengan (?P<name>.+)$"],
+    system_events: &[
+        (SystemEventKind::EncryptionNotice, r"^pesan dan panggilan terenkripsi secara end-to-end", false),
+        (SystemEventKind::GroupCreated, r#"^(?P<actor>.+?) membuat grup ["“](?P<target>.+)["”]$"#, true),
+        (SystemEventKind::JoinedViaLink, r"^(?P<actor>.+?) bergabung menggunakan tautan undangan grup ini$", true),
+        (SystemEventKind::ParticipantLeft, r"^(?P<actor>.+?) keluar$", true),
+        (SystemEventKind::ParticipantRemoved, r"^(?P<actor>.+?) mengeluarkan (?P<subjects>.+)$", false),
+        (SystemEventKind::ParticipantAdded, r"^(?P<actor>.+?) menambahkan (?P<subjects>.+)$", false),
+    ],
+};
+
+const MALAY: Table = Table {
+    code: "ms",
+    file_attached: &["(fail dilampirkan)"],
+    media_omitted: &[("<media dikecualikan>", Type::MediaOmitted)],
+    deleted: &["mesej ini telah dipadam", "anda telah memadam mesej ini"],
+    edited: &["<mesej ini telah disunting>"],
+    calls: &[
+        ("panggilan suara terlepas", false, true, false),
+        ("panggilan video terlepas", true, true, false),
+        ("panggilan suara", false, false, false),
+        ("panggilan video", true, false, false),
+    ],
+    list_separators: &[", ", " dan "],
+    chat_names: &[r"^sembang whatsapp dengan (?P<name>.+)$"],
+    system_events: &[
+        (SystemEventKind::GroupCreated, r#"^(?P<actor>.+?) mencipta kumpulan ["“](?P<target>.+)["”]$"#, true),
+        (SystemEventKind::ParticipantLeft, r"^(?P<actor>.+?) keluar$", true),
+        (SystemEventKind::ParticipantRemoved, r"^(?P<actor>.+?) mengeluarkan (?P<subjects>.+)$", false),
+        (SystemEventKind::ParticipantAdded, r"^(?P<actor>.+?) menambah (?P<subjects>.+)$", false),
+    ],
+    ..EMPTY
+};
+
+/// English first, so it wins when no other table matches better.
+const TABLES: &[&Table] = &[
+    &ENGLISH, &GERMAN, &SPANISH, &PORTUGUESE, &FRENCH, &ITALIAN, &DUTCH, &POLISH, &TURKISH, &RUSSIAN,
+    &ARABIC, &PERSIAN, &HINDI, &BENGALI, &TAMIL, &TELUGU, &MARATHI, &URDU, &INDONESIAN, &MALAY,
+];
+
+static BUILTIN: LazyLock<Vec<Locale>> = LazyLock::new(|| {
+    TABLES
+        .iter()
+        .map(|table| {
+            let mut locale = Locale::from_table(table);
+            if table.code != ENGLISH.code {
+                locale.extend(&Locale::from_table(&ENGLISH));
+            }
+            locale
+        })
+        .collect()
+});
+
+impl Locale {
+    /// Returns the built-in tables, English first.
+    pub fn builtin() -> &'static [Locale] {
+        &BUILTIN
+    }
+
+    /// Returns the built-in English table.
+    pub fn english() -> &'static Locale {
+        &BUILTIN[0]
+    }
+
+    /// Looks up a built-in table by language code, ignoring case.
+    pub fn by_code(code: &str) -> Option<&'static Locale> {
+        BUILTIN.iter().find(|l| l.code.eq_ignore_ascii_case(code))
+    }
+
+    /// Picks the built-in table that recognises the most of the sampled
+    /// message `contents`, falling back to English.
+    pub fn detect<'a, I: IntoIterator<Item = &'a str>>(contents: I) -> &'static Locale {
+        let contents: Vec<&str> = contents.into_iter().take(DETECTION_SAMPLE).collect();
+        let mut best = Self::english();
+        let mut best_score = best.score(&contents);
+        for locale in &BUILTIN[1..] {
+            let score = locale.score(&contents);
+            if score > best_score {
+                best = locale;
+                best_score = score;
+            }
+        }
+        best
+    }
+
+    /// Appends the phrases of `other` after this table's own.
+    pub fn extend(&mut self, other: &Locale) {
+        fn append<T: Clone + PartialEq>(to: &mut Vec<T>, from: &[T]) {
+            for item in from {
+                if !to.contains(item) {
+                    to.push(item.clone());
+                }
+            }
+        }
+        append(&mut self.file_attached, &other.file_attached);
+        append(&mut self.media_omitted, &other.media_omitted);
+        append(&mut self.deleted, &other.deleted);
+        append(&mut self.edited, &other.edited);
+        append(&mut self.location_prefixes, &other.location_prefixes);
+        append(&mut self.live_location, &other.live_location);
+        append(&mut self.poll_prefixes, &other.poll_prefixes);
+        append(&mut self.poll_option_prefixes, &other.poll_option_prefixes);
+        append(&mut self.poll_multi_select, &other.poll_multi_select);
+        append(&mut self.calls, &other.calls);
+        append(&mut self.list_separators, &other.list_separators);
+        self.system_events.extend(other.system_events.iter().cloned());
+        self.chat_names.extend(other.chat_names.iter().cloned());
+        self.calls.sort_by_key(|c| std::cmp::Reverse(c.phrase.chars().count()));
+    }
+
+    /// Returns `true` if `text` is only a deleted-message tombstone.
+    pub fn is_deleted(&self, text: &str) -> bool {
+        let lc = text
+            .trim_matches(|c: char| c.is_whitespace() || c == '\u{200e}' || c == '.')
+            .to_lowercase();
+        self.deleted.contains(&lc)
+    }
+
+    /// Removes a trailing "edited" marker, returning the remaining text and
+    /// whether a marker was found.
+    pub fn strip_edited(&self, text: &str) -> (String, bool) {
+        let trimmed = text.trim_end();
+        for marker in &self.edited {
+            let length = marker.chars().count();
+            if let Some((cut, _)) = trimmed.char_indices().rev().nth(length.saturating_sub(1))
+                && trimmed[cut..].to_lowercase() == *marker
+            {
+                let rest = trimmed[..cut].trim_end_matches(|c: char| c.is_whitespace() || c == '\u{200e}');
+                return (rest.to_string(), true);
+            }
+        }
+        (text.to_string(), false)
+    }
+
+    /// Returns the kind of attachment an omitted-media placeholder stands
+    /// for, or `None` if `content` is not a placeholder. Android writes one
+    /// placeholder for every kind; iOS names the kind after a U+200E mark,
+    /// sometimes preceded by the file name.
+    pub fn omitted_media_type(&self, content: &str) -> Option<Type> {
+        let lc = content.trim().to_lowercase();
+        self.media_omitted
+            .iter()
+            .find(|(marker, _)| {
+                lc.trim_start_matches('\u{200e}') == *marker
+                    || lc.strip_suffix(marker.as_str()).is_some_and(|rest| rest.ends_with('\u{200e}'))
+            })
+            .map(|(_, media_type)| *media_type)
+    }
+
+    /// Returns `true` if `content` carries an attachment marker.
+    pub fn has_attachment(&self, content: &str) -> bool {
+        let lc = content.to_lowercase();
+        lc.contains("<attached:") || self.file_attached.iter().any(|m| lc.contains(m.as_str()))
+    }
+
+    /// Extracts the file name from an Android `NAME (file attached)` line
+    /// or an iOS `<attached: NAME>` line.
+    pub fn attachment_name(&self, content: &str) -> Option<String> {
+        if let Some(pos) = self.file_attached.iter().find_map(|m| find_ignore_case(content, m)) {
+            return Some(content[..pos].trim().to_string());
+        }
+        let start = content.find("<attached:")? + "<attached:".len();
+        let end = content[start..].find('>')? + start;
+        Some(content[start..end].trim().to_string())
+    }
+
+    /// Matches a call log entry, returning its phrase and whatever follows
+    /// the comma, such as the call's length.
+    pub fn call_phrase(&self, content: &str) -> Option<(&CallPhrase, String)> {
+        let text: String = content.chars().filter(|c| *c != '\u{200e}').collect();
+        let lc = text.trim().to_lowercase();
+        self.calls.iter().find_map(|call| {
+            let rest = lc.strip_prefix(call.phrase.as_str())?;
+            if rest.is_empty() {
+                return Some((call, String::new()));
+            }
+            let detail = rest.strip_prefix([',', '،'])?;
+            Some((call, detail.trim().to_string()))
+        })
+    }
+
+    /// Reads the chat's name from an export file name such as
+    /// `WhatsApp Chat with Priya.txt`, ignoring any folder, the `.txt` or
+    /// `.zip` extension and a ` (1)` duplicate counter.
+    pub fn chat_name_from_file(&self, file_name: &str) -> Option<String> {
+        static DUPLICATE: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"\s*\(\d+\)$").expect("valid duplicate counter pattern"));
+        let base = file_name.rsplit(['/', '\\']).next().unwrap_or(file_name);
+        let lc = base.to_lowercase();
+        let stem = if lc.ends_with(".txt") || lc.ends_with(".zip") { &base[..base.len() - 4] } else { base };
+        let stem = DUPLICATE.replace(stem.trim(), "");
+        self.chat_names.iter().find_map(|pattern| {
+            let name = pattern.captures(&stem)?.name("name")?.as_str().trim();
+            (!name.is_empty()).then(|| name.to_string())
+        })
+    }
+
+    /// Returns `true` if `text` reads as a group being created or renamed.
+    /// This is a quick check; `SystemEvent::recognise` reads the new name.
+    pub fn names_group(&self, text: &str) -> bool {
+        let text = text.trim_matches(|c: char| c.is_whitespace() || c == '\u{200e}');
+        self.system_events.iter().any(|phrase| {
+            matches!(phrase.kind, SystemEventKind::GroupCreated | SystemEventKind::SubjectChanged)
+                && phrase.pattern.is_match(text)
+        })
+    }
+
+    /// Counts how many of `contents` carry one of this table's markers.
+    fn score(&self, contents: &[&str]) -> usize {
+        contents
+            .iter()
+            .filter(|content| {
+                self.is_deleted(content)
+                    || self.strip_edited(content).1
+                    || self.omitted_media_type(content).is_some()
+                    || self.file_attached.iter().any(|m| find_ignore_case(content, m).is_some())
+                    || self.call_phrase(content).is_some()
+                    || SystemEvent::recognise(content, self).is_some()
+            })
+            .count()
+    }
+
+    fn from_table(table: &Table) -> Self {
+        let strings = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let mut locale = Self {
+            code: table.code.to_string(),
+            file_attached: strings(table.file_attached),
+            media_omitted: table.media_omitted.iter().map(|(m, t)| (m.to_string(), *t)).collect(),
+            deleted: strings(table.deleted),
+            edited: strings(table.edited),
+            location_prefixes: strings(table.location_prefixes),
+            live_location: strings(table.live_location),
+            poll_prefixes: strings(table.poll_prefixes),
+            poll_option_prefixes: strings(table.poll_option_prefixes),
+            poll_multi_select: strings(table.poll_multi_select),
+            calls: table
+                .calls
+                .iter()
+                .map(|&(phrase, video, missed, group)| CallPhrase { phrase: phrase.to_string(), video, missed, group })
+                .collect(),
+            list_separators: strings(table.list_separators),
+            system_events: table
+                .system_events
+                .iter()
+                .map(|&(kind, pattern, actor_is_subject)| SystemPhrase {
+                    kind,
+                    pattern: Regex::new(&format!("(?i){}", pattern)).expect("valid system event pattern"),
+                    actor_is_subject,
+                })
+                .collect(),
+            chat_names: table
+                .chat_names
+                .iter()
+                .map(|pattern| Regex::new(&format!("(?i){}", pattern)).expect("valid chat name pattern"))
+                .collect(),
+        };
+        locale.calls.sort_by_key(|c| std::cmp::Reverse(c.phrase.chars().count()));
+        locale
+    }
+}
+
+/// Finds `needle`, which must be lowercase, in `haystack` ignoring case.
+/// The haystack is lowercased once; a match must start where one of its
+/// characters does, and its offset in `haystack` is returned.
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    let mut lower = String::with_capacity(haystack.len());
+    // The offset of each character in `lower` and in `haystack`.
+    let mut starts = Vec::with_capacity(haystack.len());
+    for (i, c) in haystack.char_indices() {
+        starts.push((lower.len(), i));
+        lower.extend(c.to_lowercase());
+    }
+    lower.match_indices(needle).find_map(|(pos, _)| {
+        let index = starts.binary_search_by_key(&pos, |&(lowered, _)| lowered).ok()?;
+        Some(starts[index].1)
+    })
+}
+
+/// Returns what follows `prefix`, which must be lowercase, at the start of
+/// `text` ignoring case. Lowercasing can change a character's length, so
+/// the text is lowered one character at a time and cut where it matched.
+pub(crate) fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    let mut lower = String::with_capacity(prefix.len());
+    for (i, c) in text.char_indices() {
+        if lower == prefix {
+            return Some(&text[i..]);
+        }
+        if !prefix.starts_with(lower.as_str()) {
+            return None;
+        }
+        lower.extend(c.to_lowercase());
+    }
+    (lower == prefix).then_some("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_markers_ignoring_case() {
+        assert_eq!(find_ignore_case("IMG-1.jpg (File Attached)", "(file attached)"), Some(10));
+        assert_eq!(find_ignore_case("Foto.JPG (DATEI ANGEHÄNGT)", "(datei angehängt)"), Some(9));
+        assert_eq!(find_ignore_case("no marker here", "(file attached)"), None);
+        // `İ` lowercases to two characters; the offset is still in the original.
+        assert_eq!(find_ignore_case("İİ.pdf (file attached)", "(file attached)"), Some(9));
+        // A match may not start inside the lowercase form of one character.
+        assert_eq!(find_ignore_case("İ", "\u{307}"), None);
+    }
+
+    #[test]
+    fn strips_prefixes_ignoring_case() {
+        assert_eq!(strip_prefix_ignore_case("Location: x", "location:"), Some(" x"));
+        assert_eq!(strip_prefix_ignore_case("POLL:", "poll:"), Some(""));
+        assert_eq!(strip_prefix_ignore_case("Poll", "poll:"), None);
+        // The Kelvin sign is three bytes but lowercases to a one-byte `k`.
+        assert_eq!(strip_prefix_ignore_case("\u{212a}onum: x", "konum:"), Some(" x"));
+        // `İ` lowercases to two characters; the cut must not fall between them.
+        assert_eq!(strip_prefix_ignore_case("İx", "i\u{307}"), Some("x"));
+        assert_eq!(strip_prefix_ignore_case("İx", "i"), None);
+    }
+
+    #[test]
+    fn detects_each_builtin_language() {
+        let samples: &[(&str, &[&str])] = &[
+            ("en", &["<Media omitted>", "IMG-1.jpg (file attached)"]),
+            ("de", &["<Medien ausgeschlossen>", "IMG-1.jpg (Datei angehängt)"]),
+            ("es", &["<Multimedia omitido>", "IMG-1.jpg (archivo adjunto)"]),
+            ("pt", &["<Mídia oculta>", "IMG-1.jpg (arquivo anexado)"]),
+            ("fr", &["<Médias omis>", "IMG-1.jpg (fichier joint)"]),
+            ("it", &["<Media omessi>", "IMG-1.jpg (file allegato)"]),
+            ("nl", &["<Media weggelaten>", "IMG-1.jpg (bestand bijgevoegd)"]),
+            ("pl", &["<Pominięto multimedia>", "IMG-1.jpg (plik załączony)"]),
+            ("tr", &["<Medya dahil edilmedi>", "IMG-1.jpg (dosya ekli)"]),
+            ("ru", &["<Без медиафайлов>", "IMG-1.jpg (файл добавлен)"]),
+            ("ar", &["<تم استبعاد الوسائط>", "IMG-1.jpg (الملف مرفق)"]),
+            ("fa", &["<رسانه حذف شد>", "IMG-1.jpg (فایل پیوست شد)"]),
+            ("hi", &["<मीडिया छोड़ दिया गया>", "IMG-1.jpg (फ़ाइल अटैच की गई)"]),
+            ("bn", &["<মিডিয়া বাদ দেওয়া হয়েছে>", "IMG-1.jpg (ফাইল সংযুক্ত)"]),
+            ("ta", &["<மீடியா தவிர்க்கப்பட்டது>", "IMG-1.jpg (கோப்பு இணைக்கப்பட்டது)"]),
+            ("te", &["<మీడియా విస్మరించబడింది>", "IMG-1.jpg (ఫైల్ జోడించబడింది)"]),
+            ("mr", &["<मीडिया वगळले>", "IMG-1.jpg (फाईल संलग्न)"]),
+            ("ur", &["<میڈیا خارج کر دیا گیا>", "IMG-1.jpg (فائل منسلک ہے)"]),
+            ("id", &["<Media tidak disertakan>", "IMG-1.jpg (file terlampir)"]),
+            ("ms", &["<Media dikecualikan>", "IMG-1.jpg (fail dilampirkan)"]),
+        ];
+        assert_eq!(samples.len(), Locale::builtin().len());
+        for (code, contents) in samples {
+            let plain = ["Hello", "See you at 5"];
+            let detected = Locale::detect(plain.iter().chain(contents.iter()).copied());
+            assert_eq!(detected.code, *code);
+        }
+    }
+
+    #[test]
+    fn falls_back_to_english() {
+        assert_eq!(Locale::detect(["Hello", "See you at 5"]).code, "en");
+        assert_eq!(Locale::detect([]).code, "en");
+    }
+}
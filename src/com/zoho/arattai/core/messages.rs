@@ -4,7 +4,6 @@ use prost_types::Timestamp;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Enumeration)]
 #[repr(i32)]
-
 pub enum Type {
     Text = 0,
     Image = 1,
@@ -12,6 +11,26 @@ pub enum Type {
     Audio = 3,
     Document = 4,
     Sticker = 5,
+    System = 6,
+    Location = 7,
+    Contact = 8,
+    Poll = 9,
+    /// Media left out of the export whose kind the transcript does not say.
+    MediaOmitted = 10,
+    Call = 11,
+}
+
+/// How trustworthy `MessageBase::timestamp` is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Enumeration)]
+#[repr(i32)]
+pub enum TimestampStatus {
+    /// Parsed directly from the message header.
+    Exact = 0,
+    /// The header could not be parsed; the time was interpolated from the
+    /// neighbouring messages.
+    Approximate = 1,
+    /// The header could not be parsed and no neighbour was available.
+    Unknown = 2,
 }
 
 #[derive(Clone, PartialEq, Message)]
@@ -22,6 +41,35 @@ pub struct MessageBase {
     pub timestamp: ::core::option::Option<Timestamp>,
     #[prost(enumeration = "Type", tag = "3")]
     pub r#type: i32,
+    #[prost(string, tag = "4")]
+    pub raw_timestamp: String,
+    #[prost(enumeration = "TimestampStatus", tag = "5")]
+    pub timestamp_status: i32,
+    /// The sender's UTC offset at `timestamp`; adding it gives the
+    /// wall-clock time shown in the transcript.
+    #[prost(sint32, tag = "6")]
+    pub utc_offset_seconds: i32,
+    /// The message was deleted; only the tombstone remained in the transcript.
+    #[prost(bool, tag = "7")]
+    pub deleted: bool,
+    /// The message was edited after it was sent.
+    #[prost(bool, tag = "8")]
+    pub edited: bool,
+    /// The export was made without media, so the attachment is absent.
+    #[prost(bool, tag = "9")]
+    pub media_omitted: bool,
+    /// The sender exactly as written in the transcript, when cleaning
+    /// invisible characters changed it; empty otherwise.
+    #[prost(string, tag = "10")]
+    pub raw_sender: String,
+    /// The message text exactly as written in the transcript, when cleaning
+    /// invisible characters changed it; empty otherwise.
+    #[prost(string, tag = "11")]
+    pub raw_text: String,
+    /// The message was sent by the user who made the export, as named by
+    /// `ParseOptions::self_name`.
+    #[prost(bool, tag = "12")]
+    pub from_self: bool,
 }
 
 #[derive(Clone, PartialEq, Message)]
@@ -46,6 +94,8 @@ pub struct ImageMessage {
     pub size: u64,
     #[prost(string, tag = "6")]
     pub extension: String,
+    #[prost(string, tag = "7")]
+    pub caption: String,
 }
 
 #[derive(Clone, PartialEq, Message)]
@@ -64,6 +114,8 @@ pub struct VideoMessage {
     pub width: u32,
     #[prost(uint32, tag = "7")]
     pub height: u32,
+    #[prost(string, tag = "8")]
+    pub caption: String,
 }
 
 #[derive(Clone, PartialEq, Message)]
@@ -90,6 +142,8 @@ pub struct DocumentMessage {
     pub extension: String,
     #[prost(uint64, tag = "4")]
     pub size: u64,
+    #[prost(string, tag = "5")]
+    pub caption: String,
 }
 
 #[derive(Clone, PartialEq, Message)]
@@ -104,12 +158,206 @@ pub struct StickerMessage {
     pub extension: String,
 }
 
+#[derive(Clone, PartialEq, Message)]
+pub struct LocationMessage {
+    #[prost(message, optional, tag = "1")]
+    pub base: ::core::option::Option<MessageBase>,
+    #[prost(double, tag = "2")]
+    pub latitude: f64,
+    #[prost(double, tag = "3")]
+    pub longitude: f64,
+    /// The place name shared with the pin, or empty for a dropped pin.
+    #[prost(string, tag = "4")]
+    pub name: String,
+    #[prost(string, repeated, tag = "5")]
+    pub address_lines: Vec<String>,
+    /// The sender shared their live location rather than a fixed pin.
+    #[prost(bool, tag = "6")]
+    pub live: bool,
+    /// The maps link exactly as it appears in the transcript.
+    #[prost(string, tag = "7")]
+    pub url: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct PhoneNumber {
+    #[prost(string, tag = "1")]
+    pub number: String,
+    /// Lowercase vCard types such as `cell`, `home` or `work`.
+    #[prost(string, repeated, tag = "2")]
+    pub types: Vec<String>,
+}
+
+/// One person from a shared `.vcf` file.
+#[derive(Clone, PartialEq, Message)]
+pub struct ContactCard {
+    #[prost(string, tag = "1")]
+    pub formatted_name: String,
+    #[prost(message, repeated, tag = "2")]
+    pub phones: Vec<PhoneNumber>,
+    #[prost(string, repeated, tag = "3")]
+    pub emails: Vec<String>,
+    #[prost(string, tag = "4")]
+    pub organisation: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ContactMessage {
+    #[prost(message, optional, tag = "1")]
+    pub base: ::core::option::Option<MessageBase>,
+    #[prost(string, tag = "2")]
+    pub name: String,
+    #[prost(uint64, tag = "3")]
+    pub size: u64,
+    /// Every card in the file; empty if the file was not included in the export.
+    #[prost(message, repeated, tag = "4")]
+    pub contacts: Vec<ContactCard>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct PollOption {
+    #[prost(string, tag = "1")]
+    pub text: String,
+    #[prost(uint32, tag = "2")]
+    pub votes: u32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct PollMessage {
+    #[prost(message, optional, tag = "1")]
+    pub base: ::core::option::Option<MessageBase>,
+    #[prost(string, tag = "2")]
+    pub question: String,
+    /// The options in the order they were offered.
+    #[prost(message, repeated, tag = "3")]
+    pub options: Vec<PollOption>,
+    /// Voters could pick more than one option.
+    #[prost(bool, tag = "4")]
+    pub multi_select: bool,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct CallMessage {
+    #[prost(message, optional, tag = "1")]
+    pub base: ::core::option::Option<MessageBase>,
+    /// A video call rather than a voice call.
+    #[prost(bool, tag = "2")]
+    pub video: bool,
+    /// Nobody picked up.
+    #[prost(bool, tag = "3")]
+    pub missed: bool,
+    /// How long the call lasted, formatted as "m:ss"; empty if not given.
+    #[prost(string, tag = "4")]
+    pub duration: String,
+    /// A call with more than one other participant.
+    #[prost(bool, tag = "5")]
+    pub group: bool,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct OmittedMediaMessage {
+    #[prost(message, optional, tag = "1")]
+    pub base: ::core::option::Option<MessageBase>,
+}
+
+/// The kind of event a sender-less system line records.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Enumeration)]
+#[repr(i32)]
+pub enum SystemEventKind {
+    /// A system line the parser does not recognise; only `text` is set.
+    Other = 0,
+    EncryptionNotice = 1,
+    GroupCreated = 2,
+    ParticipantAdded = 3,
+    ParticipantLeft = 4,
+    SecurityCodeChanged = 5,
+    ParticipantRemoved = 6,
+    JoinedViaLink = 7,
+    SubjectChanged = 8,
+    IconChanged = 9,
+    DescriptionChanged = 10,
+    AdminGranted = 11,
+    AdminRevoked = 12,
+    SettingsChanged = 13,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct SystemMessage {
+    #[prost(message, optional, tag = "1")]
+    pub base: ::core::option::Option<MessageBase>,
+    #[prost(string, tag = "2")]
+    pub text: String,
+    #[prost(enumeration = "SystemEventKind", tag = "3")]
+    pub kind: i32,
+    /// The participants who caused the event, e.g. the one who added someone.
+    #[prost(string, repeated, tag = "4")]
+    pub actors: Vec<String>,
+    /// What else the event acted on: the new group name, the new settings,
+    /// or the contact whose security code changed.
+    #[prost(string, tag = "5")]
+    pub target: String,
+    /// The participants whose membership or admin role the event changed.
+    #[prost(string, repeated, tag = "6")]
+    pub subjects: Vec<String>,
+}
+
+/// One continuous stretch of group membership reconstructed from the
+/// transcript. A missing `joined` means the participant was already in the
+/// group when the export starts; a missing `left` means they never left.
+#[derive(Clone, PartialEq, Message)]
+pub struct MembershipPeriod {
+    #[prost(string, tag = "1")]
+    pub participant: String,
+    #[prost(message, optional, tag = "2")]
+    pub joined: ::core::option::Option<Timestamp>,
+    #[prost(message, optional, tag = "3")]
+    pub left: ::core::option::Option<Timestamp>,
+}
+
 #[derive(Clone, PartialEq, Message)]
 pub struct WhatsAppMessage {
-    #[prost(oneof = "whatsapp_message::Content", tags = "1, 2, 3, 4, 5, 6")]
+    #[prost(oneof = "whatsapp_message::Content", tags = "1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12")]
     pub content: ::core::option::Option<whatsapp_message::Content>,
 }
 
+impl WhatsAppMessage {
+    /// Returns the fields shared by every message type.
+    pub fn base(&self) -> Option<&MessageBase> {
+        match self.content.as_ref()? {
+            Content::Text(m) => m.base.as_ref(),
+            Content::Image(m) => m.base.as_ref(),
+            Content::Video(m) => m.base.as_ref(),
+            Content::Audio(m) => m.base.as_ref(),
+            Content::Document(m) => m.base.as_ref(),
+            Content::Sticker(m) => m.base.as_ref(),
+            Content::System(m) => m.base.as_ref(),
+            Content::Location(m) => m.base.as_ref(),
+            Content::Contact(m) => m.base.as_ref(),
+            Content::Poll(m) => m.base.as_ref(),
+            Content::OmittedMedia(m) => m.base.as_ref(),
+            Content::Call(m) => m.base.as_ref(),
+        }
+    }
+
+    /// Returns the fields shared by every message type for modification.
+    pub fn base_mut(&mut self) -> Option<&mut MessageBase> {
+        match self.content.as_mut()? {
+            Content::Text(m) => m.base.as_mut(),
+            Content::Image(m) => m.base.as_mut(),
+            Content::Video(m) => m.base.as_mut(),
+            Content::Audio(m) => m.base.as_mut(),
+            Content::Document(m) => m.base.as_mut(),
+            Content::Sticker(m) => m.base.as_mut(),
+            Content::System(m) => m.base.as_mut(),
+            Content::Location(m) => m.base.as_mut(),
+            Content::Contact(m) => m.base.as_mut(),
+            Content::Poll(m) => m.base.as_mut(),
+            Content::OmittedMedia(m) => m.base.as_mut(),
+            Content::Call(m) => m.base.as_mut(),
+        }
+    }
+}
+
 pub mod whatsapp_message {
     use super::*;
     #[derive(Clone, PartialEq, prost::Oneof)]
@@ -126,15 +374,123 @@ pub mod whatsapp_message {
         Document(DocumentMessage),
         #[prost(message, tag = "6")]
         Sticker(StickerMessage),
+        #[prost(message, tag = "7")]
+        System(SystemMessage),
+        #[prost(message, tag = "8")]
+        Location(LocationMessage),
+        #[prost(message, tag = "9")]
+        Contact(ContactMessage),
+        #[prost(message, tag = "10")]
+        Poll(PollMessage),
+        #[prost(message, tag = "11")]
+        OmittedMedia(OmittedMediaMessage),
+        #[prost(message, tag = "12")]
+        Call(CallMessage),
     }
 }
 
 pub use whatsapp_message::Content;
 
+/// The category of a problem the parser recovered from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Enumeration)]
+#[repr(i32)]
+pub enum DiagnosticKind {
+    /// A message header whose timestamp could not be read.
+    UnparsedTimestamp = 0,
+    /// A transcript line holding bytes that are not valid in its encoding.
+    InvalidEncoding = 1,
+}
+
+/// A recoverable problem found while reading the transcript.
+#[derive(Clone, PartialEq, Message)]
+pub struct ParseDiagnostic {
+    /// The 1-based transcript line the problem was found on.
+    #[prost(uint32, tag = "1")]
+    pub line: u32,
+    #[prost(enumeration = "DiagnosticKind", tag = "2")]
+    pub kind: i32,
+    #[prost(string, tag = "3")]
+    pub message: String,
+}
+
 #[derive(Clone, PartialEq, Message)]
 pub struct WhatsAppExport {
     #[prost(string, tag = "1")]
     pub chat_name: String,
     #[prost(message, repeated, tag = "2")]
     pub messages: Vec<WhatsAppMessage>,
+    #[prost(string, tag = "3")]
+    pub date_format: String,
+    #[prost(message, repeated, tag = "4")]
+    pub diagnostics: Vec<ParseDiagnostic>,
+    #[prost(string, tag = "5")]
+    pub time_zone: String,
+    #[prost(message, repeated, tag = "6")]
+    pub roster: Vec<MembershipPeriod>,
+    /// The language code of the markers the transcript was read with.
+    #[prost(string, tag = "7")]
+    pub locale: String,
+}
+
+/// The first record of a streamed export, written ahead of its messages;
+/// see `DelimitedExport`. It carries what `WhatsAppExport` holds besides
+/// the messages and the roster.
+#[derive(Clone, PartialEq, Message)]
+pub struct ExportHeader {
+    #[prost(string, tag = "1")]
+    pub chat_name: String,
+    #[prost(string, tag = "2")]
+    pub date_format: String,
+    #[prost(string, tag = "3")]
+    pub time_zone: String,
+    #[prost(string, tag = "4")]
+    pub locale: String,
+    /// The problems known before the first message is read. Those found
+    /// while reading the messages come in the `ExportFooter`.
+    #[prost(message, repeated, tag = "5")]
+    pub diagnostics: Vec<ParseDiagnostic>,
+}
+
+/// The last record of a streamed export, written after its messages and
+/// an empty record that marks their end; see `DelimitedExport`.
+#[derive(Clone, PartialEq, Message)]
+pub struct ExportFooter {
+    /// The problems found while reading the messages, such as timestamps
+    /// that could not be read.
+    #[prost(message, repeated, tag = "1")]
+    pub diagnostics: Vec<ParseDiagnostic>,
+}
+
+/// Parser options sent across the bridges; see `ParseOptions`. Empty
+/// strings and zero limits keep the defaults.
+#[derive(Clone, PartialEq, Message)]
+pub struct ParseOptions {
+    /// Defaults to `true` when absent.
+    #[prost(bool, optional, tag = "1")]
+    pub probe_media: ::core::option::Option<bool>,
+    /// An IANA zone such as `Asia/Kolkata` or an offset such as `+05:30`.
+    #[prost(string, tag = "2")]
+    pub time_zone: String,
+    /// A pattern such as `dd/mm/yyyy, h:mm a`.
+    #[prost(string, tag = "3")]
+    pub date_format: String,
+    /// A built-in language code such as `de`.
+    #[prost(string, tag = "4")]
+    pub locale: String,
+    /// Defaults to `true` when absent.
+    #[prost(bool, optional, tag = "5")]
+    pub clean_text: ::core::option::Option<bool>,
+    #[prost(uint64, tag = "6")]
+    pub max_export_size: u64,
+    #[prost(uint64, tag = "7")]
+    pub max_transcript_size: u64,
+    #[prost(uint64, tag = "8")]
+    pub max_messages: u64,
+    #[prost(string, tag = "9")]
+    pub self_name: String,
+    #[prost(string, tag = "10")]
+    pub chat_name: String,
+    /// Defaults to `true` when absent.
+    #[prost(bool, optional, tag = "11")]
+    pub interpolate_timestamps: ::core::option::Option<bool>,
 }
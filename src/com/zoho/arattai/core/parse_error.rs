@@ -0,0 +1,102 @@
+use std::fmt;
+use std::io;
+use zip::result::ZipError;
+
+/// Why an export could not be parsed.
+///
+/// Every variant has a stable numeric code that the C, WASM and JNI
+/// bridges pass to their callers along with the message. Codes are never
+/// reused or renumbered; 0 is left for success.
+#[derive(Debug)]
+pub enum ParseError {
+    /// The input is not a readable ZIP archive.
+    NotAZip(String),
+    /// The export holds no `.txt` file that looks like a chat transcript.
+    NoTranscript,
+    /// The transcript was found but could not be read.
+    TranscriptUnreadable(String),
+    /// The transcript has no line in a WhatsApp layout this parser knows.
+    UnsupportedFormat,
+    /// Reading the export from disk failed.
+    Io(io::Error),
+    /// The export is larger than a configured limit.
+    LimitExceeded(String),
+    /// An argument passed to a bridge is missing or malformed.
+    InvalidInput(String),
+}
+
+impl ParseError {
+    /// The stable code of this kind of failure.
+    pub fn code(&self) -> u32 {
+        match self {
+            Self::NotAZip(_) => 1,
+            Self::NoTranscript => 2,
+            Self::TranscriptUnreadable(_) => 3,
+            Self::UnsupportedFormat => 4,
+            Self::Io(_) => 5,
+            Self::LimitExceeded(_) => 6,
+            Self::InvalidInput(_) => 7,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAZip(reason) => write!(f, "not a ZIP archive: {}", reason),
+            Self::NoTranscript => write!(f, "no chat transcript found in the export"),
+            Self::TranscriptUnreadable(reason) => write!(f, "could not read the chat transcript: {}", reason),
+            Self::UnsupportedFormat => write!(f, "the transcript is not in a supported WhatsApp format"),
+            Self::Io(error) => write!(f, "I/O error: {}", error),
+            Self::LimitExceeded(reason) => write!(f, "limit exceeded: {}", reason),
+            Self::InvalidInput(reason) => write!(f, "invalid input: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ParseError {
+    /// A `ParseError` carried inside the I/O error, as a size limit hit
+    /// while reading is, comes back out as it was.
+    fn from(error: io::Error) -> Self {
+        error.downcast::<Self>().unwrap_or_else(Self::Io)
+    }
+}
+
+impl From<ZipError> for ParseError {
+    fn from(error: ZipError) -> Self {
+        match error {
+            ZipError::Io(error) => Self::Io(error),
+            other => Self::NotAZip(other.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_are_stable() {
+        let cases = [
+            (ParseError::NotAZip(String::new()), 1),
+            (ParseError::NoTranscript, 2),
+            (ParseError::TranscriptUnreadable(String::new()), 3),
+            (ParseError::UnsupportedFormat, 4),
+            (ParseError::Io(io::Error::other("")), 5),
+            (ParseError::LimitExceeded(String::new()), 6),
+            (ParseError::InvalidInput(String::new()), 7),
+        ];
+        for (error, code) in cases {
+            assert_eq!(error.code(), code, "{:?}", error);
+        }
+    }
+}
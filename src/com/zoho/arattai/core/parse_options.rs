@@ -0,0 +1,273 @@
+use crate::com::zoho::arattai::core::date_format::DateFormat;
+use crate::com::zoho::arattai::core::locale::Locale;
+use crate::com::zoho::arattai::core::messages::ParseOptions as ProtoOptions;
+use crate::com::zoho::arattai::core::parse_error::ParseError;
+use crate::com::zoho::arattai::core::time_zone::ChatTimeZone;
+
+/// How `WhatsAppChatParser` reads an export.
+///
+/// The defaults detect the date format and language from the transcript,
+/// read times as UTC, clean invisible characters, interpolate unreadable
+/// timestamps, probe media files for their dimensions and durations and
+/// set no limits. Each setter returns the options, so they chain:
+///
+/// ```
+/// use rust_core::com::zoho::arattai::core::parse_options::ParseOptions;
+/// use rust_core::com::zoho::arattai::core::time_zone::ChatTimeZone;
+///
+/// let zone: ChatTimeZone = "Asia/Kolkata".parse().unwrap();
+/// let options = ParseOptions::new().time_zone(zone).probe_media(false);
+/// assert_eq!(options.get_time_zone(), zone);
+/// assert!(!options.get_probe_media());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    probe_media: bool,
+    time_zone: ChatTimeZone,
+    date_format: Option<DateFormat>,
+    locale: Option<Locale>,
+    clean_text: bool,
+    interpolate_timestamps: bool,
+    max_export_size: Option<u64>,
+    max_transcript_size: Option<u64>,
+    max_messages: Option<usize>,
+    self_name: Option<String>,
+    chat_name: Option<String>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            probe_media: true,
+            time_zone: ChatTimeZone::default(),
+            date_format: None,
+            locale: None,
+            clean_text: true,
+            interpolate_timestamps: true,
+            max_export_size: None,
+            max_transcript_size: None,
+            max_messages: None,
+            self_name: None,
+            chat_name: None,
+        }
+    }
+}
+
+impl ParseOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads options sent across a bridge. Empty strings and zero limits
+    /// keep the defaults.
+    pub fn from_proto(proto: &ProtoOptions) -> Result<Self, ParseError> {
+        let mut options = Self::new()
+            .probe_media(proto.probe_media.unwrap_or(true))
+            .clean_text(proto.clean_text.unwrap_or(true))
+            .interpolate_timestamps(proto.interpolate_timestamps.unwrap_or(true));
+        if !proto.time_zone.is_empty() {
+            options.time_zone = proto.time_zone.parse().map_err(ParseError::InvalidInput)?;
+        }
+        if !proto.date_format.is_empty() {
+            options.date_format = Some(proto.date_format.parse().map_err(ParseError::InvalidInput)?);
+        }
+        if !proto.locale.is_empty() {
+            let locale = Locale::by_code(&proto.locale)
+                .ok_or_else(|| ParseError::InvalidInput(format!("unknown locale '{}'", proto.locale)))?;
+            options.locale = Some(locale.clone());
+        }
+        options.max_export_size = (proto.max_export_size > 0).then_some(proto.max_export_size);
+        options.max_transcript_size = (proto.max_transcript_size > 0).then_some(proto.max_transcript_size);
+        if proto.max_messages > 0 {
+            let count = usize::try_from(proto.max_messages)
+                .map_err(|_| ParseError::InvalidInput(format!("max_messages {} is too large", proto.max_messages)))?;
+            options.max_messages = Some(count);
+        }
+        options.self_name = Some(proto.self_name.clone()).filter(|name| !name.is_empty());
+        options.chat_name = Some(proto.chat_name.clone()).filter(|name| !name.is_empty());
+        Ok(options)
+    }
+
+    /// Whether to open media files to read image dimensions, audio and
+    /// video durations and contact cards. Turning it off leaves those
+    /// fields empty and only indexes names and sizes.
+    pub fn probe_media(mut self, probe_media: bool) -> Self {
+        self.probe_media = probe_media;
+        self
+    }
+
+    /// The time zone the wall-clock times in the transcript are read in.
+    pub fn time_zone(mut self, time_zone: ChatTimeZone) -> Self {
+        self.time_zone = time_zone;
+        self
+    }
+
+    /// Reads every timestamp with `date_format` instead of detecting it.
+    pub fn date_format(mut self, date_format: DateFormat) -> Self {
+        self.date_format = Some(date_format);
+        self
+    }
+
+    /// Reads every marker with `locale` instead of detecting it.
+    pub fn locale(mut self, locale: Locale) -> Self {
+        self.locale = Some(locale);
+        self
+    }
+
+    /// Whether to remove invisible bidi controls from senders and text and
+    /// compose them to NFC. The raw values are kept on `MessageBase` when
+    /// they differ.
+    pub fn clean_text(mut self, clean_text: bool) -> Self {
+        self.clean_text = clean_text;
+        self
+    }
+
+    /// Whether to give messages whose timestamp could not be read a time
+    /// interpolated from their neighbours, marked `Approximate`. Turning it
+    /// off leaves them without a time, marked `Unknown`.
+    pub fn interpolate_timestamps(mut self, interpolate_timestamps: bool) -> Self {
+        self.interpolate_timestamps = interpolate_timestamps;
+        self
+    }
+
+    /// Rejects exports whose files add up to more than `bytes`. No file in
+    /// an archive is read past the size the archive declares for it.
+    pub fn max_export_size(mut self, bytes: u64) -> Self {
+        self.max_export_size = Some(bytes);
+        self
+    }
+
+    /// Rejects transcripts larger than `bytes`, whether declared so or
+    /// found so while reading.
+    pub fn max_transcript_size(mut self, bytes: u64) -> Self {
+        self.max_transcript_size = Some(bytes);
+        self
+    }
+
+    /// Rejects transcripts with more than `count` messages.
+    pub fn max_messages(mut self, count: usize) -> Self {
+        self.max_messages = Some(count);
+        self
+    }
+
+    /// The name the exporting user appears under, so that their messages
+    /// are marked `from_self`.
+    pub fn self_name(mut self, name: impl Into<String>) -> Self {
+        self.self_name = Some(name.into());
+        self
+    }
+
+    /// Names the chat instead of inferring the name from the export.
+    pub fn chat_name(mut self, name: impl Into<String>) -> Self {
+        self.chat_name = Some(name.into());
+        self
+    }
+
+    pub fn get_probe_media(&self) -> bool {
+        self.probe_media
+    }
+
+    pub fn get_time_zone(&self) -> ChatTimeZone {
+        self.time_zone
+    }
+
+    pub fn get_date_format(&self) -> Option<DateFormat> {
+        self.date_format
+    }
+
+    pub fn get_locale(&self) -> Option<&Locale> {
+        self.locale.as_ref()
+    }
+
+    pub fn get_clean_text(&self) -> bool {
+        self.clean_text
+    }
+
+    pub fn get_interpolate_timestamps(&self) -> bool {
+        self.interpolate_timestamps
+    }
+
+    pub fn get_max_export_size(&self) -> Option<u64> {
+        self.max_export_size
+    }
+
+    pub fn get_max_transcript_size(&self) -> Option<u64> {
+        self.max_transcript_size
+    }
+
+    pub fn get_max_messages(&self) -> Option<usize> {
+        self.max_messages
+    }
+
+    pub fn get_self_name(&self) -> Option<&str> {
+        self.self_name.as_deref()
+    }
+
+    pub fn get_chat_name(&self) -> Option<&str> {
+        self.chat_name.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::com::zoho::arattai::core::whats_app_parse::WhatsAppChatParser;
+    use std::io::{Cursor, Write};
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
+    /// An export of ten messages and a 4 KiB attachment.
+    fn export() -> Vec<u8> {
+        let transcript: String = (0..10).map(|i| format!("12/03/2024, 09:{:02} - Alice: message {}\n", i, i)).collect();
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, bytes) in [("_chat.txt", transcript.as_bytes()), ("notes.pdf", &[0; 4096])] {
+            writer.start_file(name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(bytes).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    /// The error code parsing `export()` with `options` fails with, or 0.
+    fn code(options: &ParseOptions) -> u32 {
+        WhatsAppChatParser::parse_bytes_with(&export(), options).map_or_else(|error| error.code(), |_| 0)
+    }
+
+    #[test]
+    fn each_limit_rejects_input_past_it() {
+        let limit_exceeded = ParseError::LimitExceeded(String::new()).code();
+        assert_eq!(code(&ParseOptions::new()), 0);
+
+        assert_eq!(code(&ParseOptions::new().max_export_size(4096)), limit_exceeded);
+        assert_eq!(code(&ParseOptions::new().max_export_size(8192)), 0);
+
+        assert_eq!(code(&ParseOptions::new().max_transcript_size(100)), limit_exceeded);
+        assert_eq!(code(&ParseOptions::new().max_transcript_size(1000)), 0);
+
+        assert_eq!(code(&ParseOptions::new().max_messages(9)), limit_exceeded);
+        assert_eq!(code(&ParseOptions::new().max_messages(10)), 0);
+    }
+
+    #[test]
+    fn each_limit_from_a_bridge_rejects_input_past_it() {
+        let limit_exceeded = ParseError::LimitExceeded(String::new()).code();
+        let limits = [
+            ProtoOptions { max_export_size: 4096, ..ProtoOptions::default() },
+            ProtoOptions { max_transcript_size: 100, ..ProtoOptions::default() },
+            ProtoOptions { max_messages: 9, ..ProtoOptions::default() },
+        ];
+        for proto in limits {
+            assert_eq!(code(&ParseOptions::from_proto(&proto).unwrap()), limit_exceeded, "{:?}", proto);
+        }
+    }
+
+    #[test]
+    fn message_limit_from_a_bridge_must_fit() {
+        let proto = ProtoOptions { max_messages: u64::MAX, ..ProtoOptions::default() };
+        match usize::try_from(u64::MAX) {
+            Ok(count) => assert_eq!(ParseOptions::from_proto(&proto).unwrap().get_max_messages(), Some(count)),
+            Err(_) => assert!(matches!(ParseOptions::from_proto(&proto), Err(ParseError::InvalidInput(_)))),
+        }
+        let proto = ProtoOptions { max_messages: 0, ..ProtoOptions::default() };
+        assert_eq!(ParseOptions::from_proto(&proto).unwrap().get_max_messages(), None);
+    }
+}
@@ -0,0 +1,139 @@
+use crate::com::zoho::arattai::core::locale::Locale;
+use crate::com::zoho::arattai::core::messages::SystemEventKind;
+
+/// The structured reading of one system line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemEvent {
+    pub kind: SystemEventKind,
+    pub actors: Vec<String>,
+    pub subjects: Vec<String>,
+    pub target: String,
+}
+
+impl SystemEvent {
+    /// Matches `text` against the system phrases of `locale`. Returns
+    /// `None` if the text is not a recognised event.
+    pub fn recognise(text: &str, locale: &Locale) -> Option<Self> {
+        let text = text.trim_matches(|c: char| c.is_whitespace() || c == '\u{200e}');
+        locale.system_events.iter().find_map(|phrase| {
+            let caps = phrase.pattern.captures(text)?;
+            let field = |name| caps.name(name).map(|m| m.as_str().trim().to_string());
+            let actors: Vec<String> = field("actor").into_iter().collect();
+            let subjects = match field("subjects") {
+                Some(list) => Self::split_participants(&list, locale),
+                None if phrase.actor_is_subject => actors.clone(),
+                None => Vec::new(),
+            };
+            Some(Self {
+                kind: phrase.kind,
+                actors,
+                subjects,
+                target: field("target").unwrap_or_default(),
+            })
+        })
+    }
+
+    /// Reads a line that is known to be a system message, falling back to
+    /// `SystemEventKind::Other` when the phrase is not recognised.
+    pub fn parse(text: &str, locale: &Locale) -> Self {
+        Self::recognise(text, locale).unwrap_or(Self {
+            kind: SystemEventKind::Other,
+            actors: Vec::new(),
+            subjects: Vec::new(),
+            target: String::new(),
+        })
+    }
+
+    /// Splits a participant list such as `Bob, Carol and Dave` on the
+    /// separators of `locale`.
+    fn split_participants(list: &str, locale: &Locale) -> Vec<String> {
+        let mut names = vec![list.to_string()];
+        for separator in &locale.list_separators {
+            names = names.iter().flat_map(|name| name.split(separator.as_str())).map(str::to_string).collect();
+        }
+        names
+            .into_iter()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn event(kind: SystemEventKind, actors: &[&str], subjects: &[&str], target: &str) -> SystemEvent {
+        SystemEvent { kind, actors: names(actors), subjects: names(subjects), target: target.to_string() }
+    }
+
+    #[test]
+    fn recognises_every_english_event() {
+        use SystemEventKind::*;
+        let en = Locale::english();
+        let cases = [
+            ("Messages and calls are end-to-end encrypted. No one outside of this chat can read them.", event(EncryptionNotice, &[], &[], "")),
+            ("Your security code with Bob changed. Tap to learn more.", event(SecurityCodeChanged, &[], &[], "Bob")),
+            ("Alice created group \"Goa Trip\"", event(GroupCreated, &["Alice"], &["Alice"], "Goa Trip")),
+            ("Alice changed the subject from \"Goa\" to \"Goa Trip\"", event(SubjectChanged, &["Alice"], &[], "Goa Trip")),
+            ("Alice changed the group name to “Goa Trip”", event(SubjectChanged, &["Alice"], &[], "Goa Trip")),
+            ("Alice changed this group's icon", event(IconChanged, &["Alice"], &[], "")),
+            ("Alice changed the group description", event(DescriptionChanged, &["Alice"], &[], "")),
+            (
+                "Alice changed this group's settings to allow only admins to send messages to this group",
+                event(SettingsChanged, &["Alice"], &[], "allow only admins to send messages to this group"),
+            ),
+            ("Bob joined using this group's invite link", event(JoinedViaLink, &["Bob"], &["Bob"], "")),
+            ("Bob left", event(ParticipantLeft, &["Bob"], &["Bob"], "")),
+            ("You're now an admin", event(AdminGranted, &[], &["You"], "")),
+            ("Bob and Carol are now admins", event(AdminGranted, &[], &["Bob", "Carol"], "")),
+            ("You're no longer an admin", event(AdminRevoked, &[], &["You"], "")),
+            ("Alice dismissed Bob as admin", event(AdminRevoked, &["Alice"], &["Bob"], "")),
+            ("Alice removed Bob", event(ParticipantRemoved, &["Alice"], &["Bob"], "")),
+            ("Alice added Bob, Carol and Dave", event(ParticipantAdded, &["Alice"], &["Bob", "Carol", "Dave"], "")),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(SystemEvent::recognise(text, en), Some(expected), "{}", text);
+        }
+    }
+
+    #[test]
+    fn recognises_german_events() {
+        use SystemEventKind::*;
+        let de = Locale::by_code("de").unwrap();
+        let cases = [
+            ("Nachrichten und Anrufe sind Ende-zu-Ende-verschlüsselt.", event(EncryptionNotice, &[], &[], "")),
+            ("Anna hat die Gruppe „Urlaub“ erstellt", event(GroupCreated, &["Anna"], &["Anna"], "Urlaub")),
+            ("Ben ist über den Einladungslink dieser Gruppe beigetreten", event(JoinedViaLink, &["Ben"], &["Ben"], "")),
+            ("Ben hat die Gruppe verlassen", event(ParticipantLeft, &["Ben"], &["Ben"], "")),
+            ("Anna hat Ben entfernt", event(ParticipantRemoved, &["Anna"], &["Ben"], "")),
+            ("Anna hat Ben, Carla und Dirk hinzugefügt", event(ParticipantAdded, &["Anna"], &["Ben", "Carla", "Dirk"], "")),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(SystemEvent::recognise(text, de), Some(expected), "{}", text);
+        }
+        // The English phrases still apply to a German export.
+        assert_eq!(SystemEvent::recognise("Ben left", de), Some(event(ParticipantLeft, &["Ben"], &["Ben"], "")));
+    }
+
+    #[test]
+    fn unknown_lines_are_other() {
+        let en = Locale::english();
+        assert_eq!(SystemEvent::recognise("The weather is nice", en), None);
+        assert_eq!(SystemEvent::parse("The weather is nice", en), event(SystemEventKind::Other, &[], &[], ""));
+    }
+
+    #[test]
+    fn splits_participant_lists() {
+        let en = Locale::english();
+        assert_eq!(SystemEvent::split_participants("Bob, Carol and Dave", en), names(&["Bob", "Carol", "Dave"]));
+        assert_eq!(SystemEvent::split_participants("Bob", en), names(&["Bob"]));
+        assert_eq!(SystemEvent::split_participants(" Bob ,  Carol ", en), names(&["Bob", "Carol"]));
+        let ar = Locale::by_code("ar").unwrap();
+        assert_eq!(SystemEvent::split_participants("سارة، علي و منى", ar), names(&["سارة", "علي", "منى"]));
+    }
+}
@@ -0,0 +1,325 @@
+use std::fmt;
+use std::io::{self, BufRead, Read};
+use unicode_normalization::UnicodeNormalization;
+
+/// The character encoding a transcript was saved in.
+///
+/// WhatsApp itself writes UTF-8, but desktop tools that re-save `_chat.txt`
+/// sometimes produce UTF-16, with or without a byte order mark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextEncoding {
+    #[default]
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+/// A transcript decoded to text, with the lines that held invalid data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedText {
+    pub text: String,
+    pub encoding: TextEncoding,
+    /// The 1-based numbers of the lines in which invalid sequences were
+    /// replaced with U+FFFD.
+    pub invalid_lines: Vec<u32>,
+}
+
+impl TextEncoding {
+    /// Reads the byte order mark if there is one, returning the encoding and
+    /// the length of the mark. Without a mark, text in which most of the
+    /// first characters have a zero high or low byte is taken as UTF-16.
+    pub fn detect(bytes: &[u8]) -> (Self, usize) {
+        if bytes.starts_with(&[0xef, 0xbb, 0xbf]) {
+            return (Self::Utf8, 3);
+        }
+        if bytes.starts_with(&[0xff, 0xfe]) {
+            return (Self::Utf16Le, 2);
+        }
+        if bytes.starts_with(&[0xfe, 0xff]) {
+            return (Self::Utf16Be, 2);
+        }
+        let sample = &bytes[..bytes.len().min(1024) & !1];
+        let pairs = sample.len() / 2;
+        if pairs == 0 {
+            return (Self::Utf8, 0);
+        }
+        let zero_high = sample.chunks_exact(2).filter(|p| p[0] != 0 && p[1] == 0).count();
+        let zero_low = sample.chunks_exact(2).filter(|p| p[0] == 0 && p[1] != 0).count();
+        if zero_high * 2 > pairs {
+            (Self::Utf16Le, 0)
+        } else if zero_low * 2 > pairs {
+            (Self::Utf16Be, 0)
+        } else {
+            (Self::Utf8, 0)
+        }
+    }
+
+    /// Detects the encoding of `bytes` and decodes them, replacing invalid
+    /// sequences instead of failing.
+    pub fn decode(bytes: &[u8]) -> DecodedText {
+        let (encoding, bom) = Self::detect(bytes);
+        let bytes = &bytes[bom..];
+        let (text, invalid_lines) = match encoding {
+            Self::Utf8 => Self::decode_utf8(bytes),
+            Self::Utf16Le => Self::decode_utf16(bytes, u16::from_le_bytes),
+            Self::Utf16Be => Self::decode_utf16(bytes, u16::from_be_bytes),
+        };
+        DecodedText { text, encoding, invalid_lines }
+    }
+
+    fn decode_utf8(bytes: &[u8]) -> (String, Vec<u32>) {
+        if let Ok(text) = std::str::from_utf8(bytes) {
+            return (text.to_string(), Vec::new());
+        }
+        let mut text = String::with_capacity(bytes.len());
+        let mut invalid_lines = Vec::new();
+        for (index, line) in bytes.split_inclusive(|b| *b == b'\n').enumerate() {
+            match std::str::from_utf8(line) {
+                Ok(line) => text.push_str(line),
+                Err(_) => {
+                    text.push_str(&String::from_utf8_lossy(line));
+                    invalid_lines.push(index as u32 + 1);
+                }
+            }
+        }
+        (text, invalid_lines)
+    }
+
+    fn decode_utf16(bytes: &[u8], read: fn([u8; 2]) -> u16) -> (String, Vec<u32>) {
+        let units = bytes.chunks_exact(2).map(|pair| read([pair[0], pair[1]]));
+        let mut text = String::with_capacity(bytes.len() / 2);
+        let mut invalid_lines = Vec::new();
+        let mut line = 1;
+        for c in char::decode_utf16(units) {
+            let c = c.unwrap_or_else(|_| {
+                if invalid_lines.last() != Some(&line) {
+                    invalid_lines.push(line);
+                }
+                char::REPLACEMENT_CHARACTER
+            });
+            if c == '\n' {
+                line += 1;
+            }
+            text.push(c);
+        }
+        if bytes.len() % 2 == 1 && invalid_lines.last() != Some(&line) {
+            text.push(char::REPLACEMENT_CHARACTER);
+            invalid_lines.push(line);
+        }
+        (text, invalid_lines)
+    }
+}
+
+/// Reads a transcript one line at a time, so that it never has to be held
+/// in memory as a whole.
+///
+/// The encoding is detected from the first kilobyte as in
+/// [`TextEncoding::decode`]. Each line comes without its line break, along
+/// with whether invalid sequences in it were replaced with U+FFFD.
+pub struct LineReader<'a> {
+    reader: Box<dyn BufRead + 'a>,
+    encoding: TextEncoding,
+    buf: Vec<u8>,
+}
+
+impl<'a> LineReader<'a> {
+    pub fn new<R: BufRead + 'a>(mut reader: R) -> io::Result<Self> {
+        let mut head = Vec::with_capacity(1024);
+        (&mut reader).take(1024).read_to_end(&mut head)?;
+        let (encoding, bom) = TextEncoding::detect(&head);
+        head.drain(..bom);
+        Ok(Self { reader: Box::new(io::Cursor::new(head).chain(reader)), encoding, buf: Vec::new() })
+    }
+
+    pub fn encoding(&self) -> TextEncoding {
+        self.encoding
+    }
+
+    /// Returns the next line and whether it held invalid data, or `None`
+    /// at the end of the text.
+    pub fn next_line(&mut self) -> io::Result<Option<(String, bool)>> {
+        self.buf.clear();
+        match self.encoding {
+            TextEncoding::Utf8 => {
+                if self.reader.read_until(b'\n', &mut self.buf)? == 0 {
+                    return Ok(None);
+                }
+                Self::trim_line_break(&mut self.buf, |bytes| bytes.last().copied().map(u16::from));
+                Ok(Some(match String::from_utf8(std::mem::take(&mut self.buf)) {
+                    Ok(line) => (line, false),
+                    Err(error) => (String::from_utf8_lossy(error.as_bytes()).into_owned(), true),
+                }))
+            }
+            TextEncoding::Utf16Le => self.next_utf16_line(u16::from_le_bytes),
+            TextEncoding::Utf16Be => self.next_utf16_line(u16::from_be_bytes),
+        }
+    }
+
+    fn next_utf16_line(&mut self, read: fn([u8; 2]) -> u16) -> io::Result<Option<(String, bool)>> {
+        let mut units = Vec::new();
+        let mut odd = false;
+        loop {
+            let mut pair = [0u8; 2];
+            let filled = Self::read_pair(&mut self.reader, &mut pair)?;
+            if filled < 2 {
+                odd = filled == 1;
+                break;
+            }
+            let unit = read(pair);
+            units.push(unit);
+            if unit == u16::from(b'\n') {
+                break;
+            }
+        }
+        if units.is_empty() && !odd {
+            return Ok(None);
+        }
+        Self::trim_line_break(&mut units, |units| units.last().copied());
+        let mut invalid = odd;
+        let mut line: String = char::decode_utf16(units)
+            .map(|c| {
+                c.unwrap_or_else(|_| {
+                    invalid = true;
+                    char::REPLACEMENT_CHARACTER
+                })
+            })
+            .collect();
+        if odd {
+            line.push(char::REPLACEMENT_CHARACTER);
+        }
+        Ok(Some((line, invalid)))
+    }
+
+    /// Reads up to two bytes, returning how many were read before the end.
+    fn read_pair(reader: &mut dyn BufRead, pair: &mut [u8; 2]) -> io::Result<usize> {
+        let mut filled = 0;
+        while filled < 2 {
+            match reader.read(&mut pair[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(filled)
+    }
+
+    /// Drops a trailing `\n` and then a trailing `\r`, as `str::lines` does.
+    fn trim_line_break<T>(line: &mut Vec<T>, last: impl Fn(&[T]) -> Option<u16>) {
+        if last(line) == Some(u16::from(b'\n')) {
+            line.pop();
+            if last(line) == Some(u16::from(b'\r')) {
+                line.pop();
+            }
+        }
+    }
+}
+
+impl fmt::Display for TextEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Utf8 => write!(f, "UTF-8"),
+            Self::Utf16Le => write!(f, "UTF-16LE"),
+            Self::Utf16Be => write!(f, "UTF-16BE"),
+        }
+    }
+}
+
+/// Removes the invisible bidi controls iOS and right-to-left exports put
+/// around names, numbers and markers (U+200E, U+200F, U+202A to U+202E,
+/// U+2066 to U+2069 and U+FEFF) and composes the rest to Unicode NFC, so
+/// the same name is always the same string.
+pub fn clean_text(text: &str) -> String {
+    text.chars()
+        .filter(|c| !matches!(c, '\u{200e}' | '\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}' | '\u{feff}'))
+        .nfc()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16(text: &str, to_bytes: fn(u16) -> [u8; 2]) -> Vec<u8> {
+        text.encode_utf16().flat_map(to_bytes).collect()
+    }
+
+    fn read_lines(bytes: &[u8]) -> (TextEncoding, Vec<(String, bool)>) {
+        let mut reader = LineReader::new(bytes).unwrap();
+        let mut lines = Vec::new();
+        while let Some(line) = reader.next_line().unwrap() {
+            lines.push(line);
+        }
+        (reader.encoding(), lines)
+    }
+
+    fn valid(lines: &[&str]) -> Vec<(String, bool)> {
+        lines.iter().map(|l| (l.to_string(), false)).collect()
+    }
+
+    #[test]
+    fn skips_a_utf8_byte_order_mark() {
+        let bytes = b"\xef\xbb\xbfAl\xc3\xad: hi\r\nbye";
+        assert_eq!(TextEncoding::detect(bytes), (TextEncoding::Utf8, 3));
+        let decoded = TextEncoding::decode(bytes);
+        assert_eq!(decoded.text, "Alí: hi\r\nbye");
+        assert!(decoded.invalid_lines.is_empty());
+        assert_eq!(read_lines(bytes), (TextEncoding::Utf8, valid(&["Alí: hi", "bye"])));
+    }
+
+    #[test]
+    fn reads_utf16_with_and_without_a_byte_order_mark() {
+        let text = "Alí: hi 👋\nbye";
+        for (encoding, to_bytes, bom) in [
+            (TextEncoding::Utf16Le, u16::to_le_bytes as fn(u16) -> [u8; 2], [0xff, 0xfe]),
+            (TextEncoding::Utf16Be, u16::to_be_bytes, [0xfe, 0xff]),
+        ] {
+            let bare = utf16(text, to_bytes);
+            let marked = [&bom[..], &bare].concat();
+            assert_eq!(TextEncoding::detect(&marked), (encoding, 2));
+            assert_eq!(TextEncoding::detect(&bare), (encoding, 0));
+            for bytes in [&marked, &bare] {
+                let decoded = TextEncoding::decode(bytes);
+                assert_eq!((decoded.text.as_str(), decoded.encoding), (text, encoding));
+                assert!(decoded.invalid_lines.is_empty());
+                assert_eq!(read_lines(bytes), (encoding, valid(&["Alí: hi 👋", "bye"])));
+            }
+        }
+    }
+
+    #[test]
+    fn replaces_an_odd_trailing_byte_in_utf16() {
+        let mut bytes = utf16("a\nb", u16::to_le_bytes);
+        bytes.push(b'c');
+        let decoded = TextEncoding::decode(&bytes);
+        assert_eq!(decoded.text, "a\nb\u{fffd}");
+        assert_eq!(decoded.invalid_lines, [2]);
+        let (_, lines) = read_lines(&bytes);
+        assert_eq!(lines, [("a".to_string(), false), ("b\u{fffd}".to_string(), true)]);
+
+        // An unpaired surrogate is replaced the same way.
+        let mut bytes = utf16("a\n", u16::to_be_bytes);
+        bytes.extend([0xd8, 0x00, 0x00, b'b']);
+        assert_eq!(TextEncoding::decode(&bytes).invalid_lines, [2]);
+    }
+
+    #[test]
+    fn reports_the_line_of_invalid_utf8() {
+        let bytes = b"one\ntwo \xff\nthree\nfour \xc3\n";
+        let decoded = TextEncoding::decode(bytes);
+        assert_eq!(decoded.text, "one\ntwo \u{fffd}\nthree\nfour \u{fffd}\n");
+        assert_eq!(decoded.invalid_lines, [2, 4]);
+        let (_, lines) = read_lines(bytes);
+        let invalid: Vec<_> = lines.iter().enumerate().filter(|(_, (_, bad))| *bad).map(|(i, _)| i + 1).collect();
+        assert_eq!(invalid, [2, 4]);
+        assert_eq!(lines[1].0, "two \u{fffd}");
+    }
+
+    #[test]
+    fn cleans_bidi_controls_and_composes_text() {
+        assert_eq!(clean_text("\u{200e}Alice\u{200f}: \u{202a}+1 555\u{202c}\u{2069}\u{feff}"), "Alice: +1 555");
+        // `e` and a combining acute accent compose to `é`.
+        assert_eq!(clean_text("Rene\u{301}e"), "Renée");
+        assert_eq!(clean_text("plain"), "plain");
+    }
+}
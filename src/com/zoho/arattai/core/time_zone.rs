@@ -0,0 +1,151 @@
+use chrono::{DateTime, Duration, FixedOffset, LocalResult, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::fmt;
+use std::str::FromStr;
+
+/// The time zone of the phone that produced an export.
+///
+/// Transcript headers carry the sender's wall-clock time with no offset.
+/// The parser uses a `ChatTimeZone` to turn that wall-clock time into a true
+/// instant. The default, `Utc`, reads wall-clock times as if they were UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChatTimeZone {
+    #[default]
+    Utc,
+    /// A constant offset such as `+05:30`.
+    Fixed(FixedOffset),
+    /// An IANA zone such as `Asia/Kolkata`, which follows DST transitions.
+    Named(Tz),
+}
+
+impl ChatTimeZone {
+    /// Converts a wall-clock time into an instant, returning it together
+    /// with the UTC offset in seconds that was applied.
+    ///
+    /// A time repeated by a DST fall-back resolves to its first occurrence.
+    /// A time skipped by a spring-forward gap is moved one hour later,
+    /// which is what the phone clock showed at that instant.
+    pub fn to_instant(&self, local: NaiveDateTime) -> Option<(DateTime<Utc>, i32)> {
+        match self {
+            Self::Utc => Some((local.and_utc(), 0)),
+            Self::Fixed(offset) => Self::resolve(offset, local),
+            Self::Named(tz) => Self::resolve(tz, local),
+        }
+    }
+
+    fn resolve<Z: TimeZone>(zone: &Z, local: NaiveDateTime) -> Option<(DateTime<Utc>, i32)> {
+        let dt = match zone.from_local_datetime(&local) {
+            LocalResult::Single(dt) => dt,
+            LocalResult::Ambiguous(earliest, _) => earliest,
+            LocalResult::None => zone.from_local_datetime(&(local + Duration::hours(1))).earliest()?,
+        };
+        let offset = dt.offset().fix().local_minus_utc();
+        Some((dt.with_timezone(&Utc), offset))
+    }
+
+    /// Reads `+05:30`, `+0530` or `+05` into a fixed offset.
+    fn parse_offset(s: &str) -> Option<FixedOffset> {
+        let sign = if s.starts_with('-') { -1 } else { 1 };
+        let digits: String = s[1..].chars().filter(|c| *c != ':').collect();
+        if !digits.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let (hours, minutes) = match digits.len() {
+            1 | 2 => (digits.parse::<i32>().ok()?, 0),
+            4 => (digits[..2].parse::<i32>().ok()?, digits[2..].parse::<i32>().ok()?),
+            _ => return None,
+        };
+        if minutes >= 60 {
+            return None;
+        }
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+    }
+}
+
+impl fmt::Display for ChatTimeZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Utc => write!(f, "UTC"),
+            Self::Fixed(offset) => write!(f, "{}", offset),
+            Self::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+
+impl FromStr for ChatTimeZone {
+    type Err = String;
+
+    /// Accepts `UTC`, a fixed offset such as `+05:30` or `-0800`, or an IANA
+    /// zone name such as `Europe/Berlin`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("utc") || s.eq_ignore_ascii_case("z") {
+            return Ok(Self::Utc);
+        }
+        if s.starts_with(['+', '-']) {
+            return Self::parse_offset(s).map(Self::Fixed).ok_or_else(|| format!("invalid UTC offset \"{}\"", s));
+        }
+        s.parse::<Tz>().map(Self::Named).map_err(|_| format!("unknown time zone \"{}\"", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(zone: &str, local: &str) -> (String, i32) {
+        let zone: ChatTimeZone = zone.parse().unwrap();
+        let local = NaiveDateTime::parse_from_str(local, "%Y-%m-%d %H:%M").unwrap();
+        let (instant, offset) = zone.to_instant(local).unwrap();
+        (instant.format("%Y-%m-%d %H:%M").to_string(), offset)
+    }
+
+    #[test]
+    fn reads_wall_clock_times_as_utc_by_default() {
+        assert_eq!(ChatTimeZone::default(), ChatTimeZone::Utc);
+        assert_eq!(at("UTC", "2024-03-12 09:30"), ("2024-03-12 09:30".to_string(), 0));
+        assert_eq!("z".parse::<ChatTimeZone>(), Ok(ChatTimeZone::Utc));
+    }
+
+    #[test]
+    fn applies_fixed_offsets() {
+        assert_eq!(at("+05:30", "2024-03-12 09:30"), ("2024-03-12 04:00".to_string(), 19_800));
+        assert_eq!(at("-08:00", "2024-03-12 20:00"), ("2024-03-13 04:00".to_string(), -28_800));
+        assert_eq!(at("+0530", "2024-03-12 09:30"), at("+05:30", "2024-03-12 09:30"));
+        assert_eq!(at("-08", "2024-03-12 20:00"), at("-08:00", "2024-03-12 20:00"));
+    }
+
+    #[test]
+    fn follows_named_zones_across_dst() {
+        assert_eq!(at("Asia/Kolkata", "2024-03-12 09:30"), ("2024-03-12 04:00".to_string(), 19_800));
+        assert_eq!(at("Europe/Berlin", "2024-01-15 12:00"), ("2024-01-15 11:00".to_string(), 3_600));
+        assert_eq!(at("Europe/Berlin", "2024-07-15 12:00"), ("2024-07-15 10:00".to_string(), 7_200));
+    }
+
+    #[test]
+    fn moves_a_time_in_a_dst_gap_one_hour_later() {
+        // Berlin clocks jumped from 02:00 to 03:00 on 31 March 2024.
+        assert_eq!(at("Europe/Berlin", "2024-03-31 02:30"), ("2024-03-31 01:30".to_string(), 7_200));
+    }
+
+    #[test]
+    fn resolves_a_time_in_a_dst_overlap_to_its_first_occurrence() {
+        // Berlin clocks showed 02:00 to 03:00 twice on 27 October 2024.
+        assert_eq!(at("Europe/Berlin", "2024-10-27 02:30"), ("2024-10-27 00:30".to_string(), 7_200));
+    }
+
+    #[test]
+    fn rejects_invalid_zones() {
+        for zone in ["+25:00", "+05:75", "+5:3", "+05:3x", "+", "-", "Mars/Olympus", ""] {
+            assert!(zone.parse::<ChatTimeZone>().is_err(), "{zone}");
+        }
+    }
+
+    #[test]
+    fn display_and_from_str_round_trip() {
+        for zone in ["UTC", "+05:30", "-08:00", "Europe/Berlin"] {
+            let parsed: ChatTimeZone = zone.parse().unwrap();
+            assert_eq!(parsed.to_string(), zone);
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use crate::com::zoho::arattai::core::messages::{ContactCard, PhoneNumber};
+
+/// Parses every `BEGIN:VCARD` ... `END:VCARD` block in `data`.
+///
+/// Handles vCard 2.1, 3.0 and 4.0 as WhatsApp writes them: folded lines,
+/// `TYPE=` and bare type parameters, `item1.` group prefixes, backslash
+/// escapes and the quoted-printable values older Android phones produce.
+/// Properties other than FN, N, TEL, EMAIL and ORG are ignored.
+pub fn parse_vcards(data: &str) -> Vec<ContactCard> {
+    let mut cards = Vec::new();
+    let mut current: Option<ContactCard> = None;
+    let mut structured_name = String::new();
+    for line in unfold(data) {
+        let Some((key, value)) = line.split_once(':') else { continue };
+        let mut params = key.split(';');
+        let property = params.next().unwrap_or_default();
+        let property = property.rsplit('.').next().unwrap_or(property).to_uppercase();
+        let params: Vec<String> = params.map(|p| p.to_uppercase()).collect();
+        let value = if params.iter().any(|p| p == "ENCODING=QUOTED-PRINTABLE" || p == "QUOTED-PRINTABLE") {
+            decode_quoted_printable(value)
+        } else {
+            value.to_string()
+        };
+
+        match property.as_str() {
+            "BEGIN" if value.eq_ignore_ascii_case("VCARD") => {
+                current = Some(ContactCard::default());
+                structured_name.clear();
+            }
+            "END" if value.eq_ignore_ascii_case("VCARD") => {
+                if let Some(mut card) = current.take() {
+                    if card.formatted_name.is_empty() {
+                        card.formatted_name = structured_name.clone();
+                    }
+                    cards.push(card);
+                }
+            }
+            _ => {
+                let Some(card) = current.as_mut() else { continue };
+                match property.as_str() {
+                    "FN" => card.formatted_name = unescape(&value),
+                    "N" => {
+                        // N is family;given;additional;prefix;suffix.
+                        let parts: Vec<String> = split_unescaped(&value).iter().map(|p| unescape(p)).collect();
+                        let order = [3, 1, 2, 0, 4];
+                        structured_name = order
+                            .iter()
+                            .filter_map(|&i| parts.get(i))
+                            .filter(|p| !p.is_empty())
+                            .cloned()
+                            .collect::<Vec<_>>()
+                            .join(" ");
+                    }
+                    "TEL" => card.phones.push(PhoneNumber {
+                        number: unescape(&value),
+                        types: phone_types(&params),
+                    }),
+                    "EMAIL" => card.emails.push(unescape(&value)),
+                    "ORG" => {
+                        card.organisation = split_unescaped(&value)
+                            .iter()
+                            .map(|p| unescape(p))
+                            .filter(|p| !p.is_empty())
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    cards
+}
+
+/// Joins folded lines (a line starting with a space or tab continues the
+/// previous one) and quoted-printable soft line breaks (a trailing `=`).
+fn unfold(data: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut soft_break = false;
+    for raw in data.lines() {
+        let raw = raw.trim_end_matches('\r');
+        match lines.last_mut() {
+            Some(last) if soft_break => last.push_str(raw.trim_start()),
+            Some(last) if raw.starts_with([' ', '\t']) => last.push_str(&raw[1..]),
+            _ => lines.push(raw.to_string()),
+        }
+        soft_break = raw.ends_with('=') && lines.last().is_some_and(|l| l.to_uppercase().contains("QUOTED-PRINTABLE"));
+        if soft_break && let Some(last) = lines.last_mut() {
+            last.pop();
+        }
+    }
+    lines
+}
+
+/// Reads the phone type from `TYPE=CELL,VOICE`, `TYPE=cell` or bare `CELL`
+/// parameters, lowercased and without the generic `voice`/`pref` types.
+fn phone_types(params: &[String]) -> Vec<String> {
+    params
+        .iter()
+        .flat_map(|p| {
+            let (name, value) = p.split_once('=').unwrap_or(("TYPE", p));
+            if name == "TYPE" { value.split(',').collect::<Vec<_>>() } else { Vec::new() }
+        })
+        .map(|t| t.trim_matches('"').to_lowercase())
+        .filter(|t| !matches!(t.as_str(), "" | "voice" | "pref" | "quoted-printable"))
+        .collect()
+}
+
+/// Splits a structured value on `;` separators that are not escaped.
+fn split_unescaped(value: &str) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut escaped = false;
+    for c in value.chars() {
+        let part = parts.last_mut().expect("parts is never empty");
+        if escaped {
+            part.push('\\');
+            part.push(c);
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == ';' {
+            parts.push(String::new());
+        } else {
+            part.push(c);
+        }
+    }
+    parts
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out.trim().to_string()
+}
+
+fn decode_quoted_printable(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'='
+            && let Some(hex) = value.get(i + 1..i + 3)
+            && let Ok(byte) = u8::from_str_radix(hex, 16)
+        {
+            out.push(byte);
+            i += 3;
+            continue;
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn phone(number: &str, types: &[&str]) -> PhoneNumber {
+        PhoneNumber { number: number.to_string(), types: types.iter().map(|t| t.to_string()).collect() }
+    }
+
+    #[test]
+    fn reads_every_card_in_a_file() {
+        let data = "\
+BEGIN:VCARD\r\nVERSION:3.0\r\nFN:Alice Smith\r\nTEL;TYPE=CELL:+1 555 0100\r\nEND:VCARD\r\n\
+BEGIN:VCARD\r\nVERSION:3.0\r\nFN:Bob\r\nEMAIL:bob@example.com\r\nORG:Acme;Sales\r\nEND:VCARD\r\n";
+        let cards = parse_vcards(data);
+        assert_eq!(cards.len(), 2);
+        assert_eq!(cards[0].formatted_name, "Alice Smith");
+        assert_eq!(cards[0].phones, vec![phone("+1 555 0100", &["cell"])]);
+        assert_eq!(cards[1].formatted_name, "Bob");
+        assert_eq!(cards[1].emails, vec!["bob@example.com".to_string()]);
+        assert_eq!(cards[1].organisation, "Acme, Sales");
+    }
+
+    #[test]
+    fn joins_folded_lines() {
+        let data = "BEGIN:VCARD\nFN:Alexandra\n  Smith-Jones\nNOTE:ignored\nEND:VCARD\n";
+        assert_eq!(parse_vcards(data)[0].formatted_name, "Alexandra Smith-Jones");
+    }
+
+    #[test]
+    fn decodes_quoted_printable_values() {
+        let data = "\
+BEGIN:VCARD\nVERSION:2.1\n\
+FN;CHARSET=UTF-8;ENCODING=QUOTED-PRINTABLE:Ren=C3=A9e =\n\
+M=C3=BCller\n\
+END:VCARD\n";
+        assert_eq!(parse_vcards(data)[0].formatted_name, "Renée Müller");
+    }
+
+    #[test]
+    fn falls_back_to_the_structured_name() {
+        let data = "BEGIN:VCARD\nN:Smith;Alice;Jane;Dr.;PhD\nEND:VCARD\n";
+        assert_eq!(parse_vcards(data)[0].formatted_name, "Dr. Alice Jane Smith PhD");
+
+        let data = "BEGIN:VCARD\nN:Smith\\;Jones;Alice;;;\nFN:\nEND:VCARD\n";
+        assert_eq!(parse_vcards(data)[0].formatted_name, "Alice Smith;Jones");
+    }
+
+    #[test]
+    fn keeps_each_phone_with_its_types() {
+        let data = "\
+BEGIN:VCARD\n\
+FN:Alice\n\
+TEL;TYPE=CELL,VOICE;TYPE=pref:+1 555 0100\n\
+item1.TEL;HOME:+1 555 0101\n\
+TEL;type=\"work\":+1 555 0102\n\
+TEL:+1 555 0103\n\
+END:VCARD\n";
+        assert_eq!(
+            parse_vcards(data)[0].phones,
+            vec![
+                phone("+1 555 0100", &["cell"]),
+                phone("+1 555 0101", &["home"]),
+                phone("+1 555 0102", &["work"]),
+                phone("+1 555 0103", &[]),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_malformed_and_empty_input() {
+        assert!(parse_vcards("").is_empty());
+        assert!(parse_vcards("not a vcard at all\n").is_empty());
+        // Properties outside a card and a card that never ends are dropped.
+        assert!(parse_vcards("FN:Stray\nBEGIN:VCARD\nFN:Unfinished\n").is_empty());
+        let cards = parse_vcards("BEGIN:VCARD\nno colon here\nFN:Alice\nEND:VCARD\nEND:VCARD\n");
+        assert_eq!(cards.len(), 1);
+        assert_eq!(cards[0].formatted_name, "Alice");
+    }
+}
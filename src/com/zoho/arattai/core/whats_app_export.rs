@@ -1,4 +1,10 @@
-use crate::com::zoho::arattai::core::messages::WhatsAppMessage;
+use crate::com::zoho::arattai::core::date_format::DateFormat;
+use crate::com::zoho::arattai::core::group_roster::GroupRoster;
+use crate::com::zoho::arattai::core::messages::{
+    whatsapp_message, ParseDiagnostic, SystemEventKind, SystemMessage,
+    WhatsAppExport as ProtoExport, WhatsAppMessage,
+};
+use crate::com::zoho::arattai::core::time_zone::ChatTimeZone;
 
 /// Immutable container for a fully-parsed WhatsApp chat export.
 ///
@@ -13,14 +19,45 @@ pub struct WhatsAppExport {
     /// All messages in chronological order exactly as they appear
     /// in the WhatsApp transcript file.
     messages: Vec<WhatsAppMessage>,
+
+    /// The timestamp layout the transcript was read with, either detected
+    /// from its header lines or supplied by the caller.
+    date_format: DateFormat,
+
+    /// The zone the transcript's wall-clock times were interpreted in.
+    time_zone: ChatTimeZone,
+
+    /// The code of the language table the transcript's markers were read
+    /// with, such as `en`.
+    locale: String,
+
+    /// Every recoverable problem found while reading the transcript,
+    /// in transcript order.
+    diagnostics: Vec<ParseDiagnostic>,
+
+    /// Group membership over time, reconstructed from `messages`.
+    roster: GroupRoster,
 }
 
 impl WhatsAppExport {
     /// Creates a new `WhatsAppExport`.
-    pub fn new(chat_name: String, messages: Vec<WhatsAppMessage>) -> Self {
+    pub fn new(
+        chat_name: String,
+        messages: Vec<WhatsAppMessage>,
+        date_format: DateFormat,
+        time_zone: ChatTimeZone,
+        locale: String,
+        diagnostics: Vec<ParseDiagnostic>,
+    ) -> Self {
+        let roster = GroupRoster::reconstruct(&messages);
         Self {
             chat_name,
             messages,
+            date_format,
+            time_zone,
+            locale,
+            diagnostics,
+            roster,
         }
     }
 
@@ -33,4 +70,61 @@ impl WhatsAppExport {
     pub fn get_chat_name(&self) -> &str {
         &self.chat_name
     }
+
+    /// Returns the timestamp layout used to read the transcript.
+    pub fn get_date_format(&self) -> DateFormat {
+        self.date_format
+    }
+
+    /// Returns the zone the transcript's wall-clock times were read in.
+    pub fn get_time_zone(&self) -> ChatTimeZone {
+        self.time_zone
+    }
+
+    /// Returns the code of the language table used to read the transcript.
+    pub fn get_locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// Returns the problems the parser recovered from, such as headers
+    /// whose timestamp could not be read.
+    pub fn get_diagnostics(&self) -> &[ParseDiagnostic] {
+        &self.diagnostics
+    }
+
+    /// Returns the group membership and admin events in transcript order,
+    /// leaving out notices such as the encryption banner.
+    pub fn get_group_events(&self) -> Vec<&SystemMessage> {
+        self.messages
+            .iter()
+            .filter_map(|msg| match msg.content.as_ref()? {
+                whatsapp_message::Content::System(m) => Some(m),
+                _ => None,
+            })
+            .filter(|m| {
+                !matches!(
+                    SystemEventKind::try_from(m.kind),
+                    Ok(SystemEventKind::Other | SystemEventKind::EncryptionNotice | SystemEventKind::SecurityCodeChanged)
+                )
+            })
+            .collect()
+    }
+
+    /// Returns who was in the group over time; see `GroupRoster::members_at`.
+    pub fn get_roster(&self) -> &GroupRoster {
+        &self.roster
+    }
+
+    /// Converts the export into the Protobuf message sent across the bridges.
+    pub fn to_proto(&self) -> ProtoExport {
+        ProtoExport {
+            chat_name: self.chat_name.clone(),
+            messages: self.messages.clone(),
+            date_format: self.date_format.to_string(),
+            diagnostics: self.diagnostics.clone(),
+            time_zone: self.time_zone.to_string(),
+            roster: self.roster.get_periods().to_vec(),
+            locale: self.locale.clone(),
+        }
+    }
 }
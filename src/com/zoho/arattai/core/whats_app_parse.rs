@@ -1,49 +1,180 @@
 use crate::com::zoho::arattai::core::messages::{
-    whatsapp_message, AudioMessage, DocumentMessage, ImageMessage, MessageBase, StickerMessage,
-    TextMessage, Type, VideoMessage, WhatsAppMessage,
+    whatsapp_message, AudioMessage, CallMessage, ContactMessage, DiagnosticKind, DocumentMessage, ImageMessage,
+    LocationMessage, MessageBase, OmittedMediaMessage, ParseDiagnostic, PollMessage, PollOption, StickerMessage, SystemEventKind, SystemMessage, TextMessage,
+    TimestampStatus, Type, VideoMessage, WhatsAppMessage,
 };
+use crate::com::zoho::arattai::core::date_format::{DateFormat, DateFormatDetector};
+use crate::com::zoho::arattai::core::locale::{self, Locale};
+use crate::com::zoho::arattai::core::parse_error::ParseError;
+use crate::com::zoho::arattai::core::parse_options::ParseOptions;
+use crate::com::zoho::arattai::core::system_event::SystemEvent;
+use crate::com::zoho::arattai::core::text_encoding::{self, LineReader, TextEncoding};
+use crate::com::zoho::arattai::core::time_zone::ChatTimeZone;
+use crate::com::zoho::arattai::core::vcard;
 use crate::com::zoho::arattai::core::whats_app_export::WhatsAppExport;
 use chrono::{DateTime, NaiveDateTime, Utc};
 use regex::Regex;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::hash::{BuildHasher, RandomState};
+use std::marker::PhantomData;
 #[cfg(not(target_arch = "wasm32"))]
 use std::fs::File;
-use std::io::{self, BufRead, BufReader, Read};
+use std::io::{self, BufRead, BufReader, Read, Seek};
 #[cfg(not(target_arch = "wasm32"))]
-use std::io::{Seek, SeekFrom};
+use std::io::SeekFrom;
 use std::path::Path;
 #[cfg(not(target_arch = "wasm32"))]
 use std::path::PathBuf;
+use std::sync::{Arc, LazyLock};
 
 #[cfg(not(target_arch = "wasm32"))]
 use tempfile::NamedTempFile;
 
+use ouroboros::self_referencing;
 use zip::ZipArchive;
 
+/// The timestamp at the start of every header line. The date fields may be
+/// separated by `/`, `.` or `-` in any order, and the clock may be 24-hour.
+/// Digits may be in any script, and the AM/PM marker may be localized and
+/// come before the time, as in `2024/03/12 午後9:01`.
+const TIMESTAMP_PATTERN: &str = r"\d{1,4}\u{200f}?[./-]\d{1,2}\u{200f}?[./-]\d{1,4}[,،]?[\s\u{202f}\u{00a0}\u{200f}]+(?:(?:午前|午後|上午|下午|오전|오후)\s*)?\d{1,2}:\d{2}(?::\d{2})?(?:[\s\u{202f}\u{00a0}\u{200f}]*(?:[ap]\.?\s?m\.?|ص|م|पूर्वाह्न|अपराह्न))?";
+
+/// The chat name used when nothing in the export names the chat.
+const DEFAULT_CHAT_NAME: &str = "WhatsApp Chat";
+
+/// How much of each `.txt` file is sampled for header lines when looking
+/// for the transcript.
+const TRANSCRIPT_SAMPLE_BYTES: usize = 8192;
+const TRANSCRIPT_SAMPLE_LINES: usize = 20;
+
+/// How many messages without a time are held back at most to be given
+/// one interpolated from their neighbours.
+const MAX_UNDATED: usize = 1000;
+
+/// How many possible senders `Participants` counts at most.
+const MAX_SENDER_CANDIDATES: usize = 1 << 16;
+
+/// How many of the latest lines that may name the group are kept to name
+/// the chat.
+const MAX_SUBJECT_LINES: usize = 64;
+
+/// Localized AM/PM markers and the English marker each stands for.
+const MERIDIEM_MARKERS: &[(&str, &str)] = &[
+    ("午前", "am"),
+    ("午後", "pm"),
+    ("上午", "am"),
+    ("下午", "pm"),
+    ("오전", "am"),
+    ("오후", "pm"),
+    ("पूर्वाह्न", "am"),
+    ("अपराह्न", "pm"),
+    ("ص", "am"),
+    ("م", "pm"),
+];
+
+/// The zero of each digit script WhatsApp writes timestamps in besides
+/// ASCII: Arabic-Indic, Extended Arabic-Indic (Persian and Urdu),
+/// Devanagari, Bengali, Gurmukhi, Gujarati, Odia, Tamil, Telugu, Kannada,
+/// Malayalam, Thai, Myanmar and fullwidth.
+const DIGIT_ZEROS: &[char] = &[
+    '\u{0660}', '\u{06f0}', '\u{0966}', '\u{09e6}', '\u{0a66}', '\u{0ae6}', '\u{0b66}', '\u{0be6}', '\u{0c66}',
+    '\u{0ce6}', '\u{0d66}', '\u{0e50}', '\u{1040}', '\u{ff10}',
+];
+
+/// Header line of an Android export, `12/03/2024, 9:01 am - Sender: text`
+/// or, for a system event, `12/03/2024, 9:01 am - Alice added Bob`. The
+/// sender is left in the text, since names may contain colons.
+static ANDROID_HEADER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(r"(?i)^\u{{200e}}?({})\s*-\s*(.+)$", TIMESTAMP_PATTERN))
+        .expect("valid Android header pattern")
+});
+
+/// Header line of an iOS export: `[12/03/24, 09:01:33] Sender: text`.
+static IOS_HEADER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(r"(?i)^\u{{200e}}?\[({})\]\s*(.+)$", TIMESTAMP_PATTERN))
+        .expect("valid iOS header pattern")
+});
+
+/// A Google or Apple Maps link as WhatsApp writes it for a shared location.
+static MAPS_URL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^https?://(?:www\.)?(?:maps\.google\.[a-z.]+|google\.[a-z.]+/maps|maps\.apple\.com|maps\.app\.goo\.gl|goo\.gl/maps)\S*$")
+        .expect("valid maps URL pattern")
+});
+
+/// The latitude and longitude inside a maps link: `?q=12.97,77.59`,
+/// `&ll=12.97%2C77.59` or `@12.97,77.59`.
+static COORDINATES: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:[?&](?:q|query|ll)=|@)(-?\d{1,3}(?:\.\d+)?)(?:,|%2C)\s*(-?\d{1,3}(?:\.\d+)?)")
+        .expect("valid coordinates pattern")
+});
+
+/// A poll option with its tally, e.g. `Goa (3 votes)`. The unit after the
+/// count is not checked so translated tallies match too.
+static POLL_TALLY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(.*?)\s*\((\d+)\s*[\p{L}\s]*\)$").expect("valid poll tally pattern")
+});
+
+/// One part of a call duration such as `1 hr` or `12 min`.
+static CALL_DURATION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)(\d+)\s*(h|hr|hrs|hour|hours|m|min|mins|minute|minutes|s|sec|secs|second|seconds)\b")
+        .expect("valid call duration pattern")
+});
+
+/// The captures shared by every supported header format. System events
+/// have no sender.
+struct Header<'a> {
+    timestamp: &'a str,
+    sender: Option<&'a str>,
+    content: &'a str,
+}
+
+/// The bytes of a ZIP export held in memory, either borrowed from the
+/// caller or owned by the parser. Cloning is cheap either way, so each
+/// entry can be read through a cursor of its own.
+#[derive(Clone)]
+enum ZipBytes<'a> {
+    Borrowed(&'a [u8]),
+    Owned(Arc<Vec<u8>>),
+}
+
+impl AsRef<[u8]> for ZipBytes<'_> {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::Borrowed(bytes) => bytes,
+            Self::Owned(bytes) => bytes,
+        }
+    }
+}
+
 enum DataSource<'a> {
-    ZipCursor(ZipArchive<io::Cursor<&'a [u8]>>),
+    ZipCursor(ZipArchive<io::Cursor<ZipBytes<'a>>>),
+    /// An archive on disk and its path, from which entries can be opened
+    /// again independently of the archive.
     #[cfg(not(target_arch = "wasm32"))]
-    ZipFile(ZipArchive<File>),
+    ZipFile(ZipArchive<File>, PathBuf),
     #[cfg(not(target_arch = "wasm32"))]
     Dir(PathBuf),
 }
 
 impl<'a> DataSource<'a> {
-    #[cfg(not(target_arch = "wasm32"))]
     fn read_to_vec(&mut self, name: &str) -> io::Result<Vec<u8>> {
         match self {
             Self::ZipCursor(archive) => {
-                let mut entry = archive.by_name(name).map_err(|e| io::Error::new(io::ErrorKind::NotFound, e))?;
+                let entry = archive.by_name(name).map_err(|e| io::Error::new(io::ErrorKind::NotFound, e))?;
+                let size = entry.size();
                 let mut buf = Vec::new();
-                entry.read_to_end(&mut buf)?;
+                Limited::declared(BufReader::new(entry), name, size).read_to_end(&mut buf)?;
                 Ok(buf)
             }
-            Self::ZipFile(archive) => {
-                let mut entry = archive.by_name(name).map_err(|e| io::Error::new(io::ErrorKind::NotFound, e))?;
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::ZipFile(archive, _) => {
+                let entry = archive.by_name(name).map_err(|e| io::Error::new(io::ErrorKind::NotFound, e))?;
+                let size = entry.size();
                 let mut buf = Vec::new();
-                entry.read_to_end(&mut buf)?;
+                Limited::declared(BufReader::new(entry), name, size).read_to_end(&mut buf)?;
                 Ok(buf)
             }
+            #[cfg(not(target_arch = "wasm32"))]
             Self::Dir(path) => {
                 let file_path = path.join(name);
                 std::fs::read(file_path)
@@ -55,18 +186,20 @@ impl<'a> DataSource<'a> {
     fn get_file_handle(&mut self, name: &str, tmp: &mut Option<NamedTempFile>) -> io::Result<File> {
         match self {
             Self::ZipCursor(archive) => {
-                let mut entry = archive.by_name(name).map_err(|e| io::Error::new(io::ErrorKind::NotFound, e))?;
+                let entry = archive.by_name(name).map_err(|e| io::Error::new(io::ErrorKind::NotFound, e))?;
+                let size = entry.size();
                 let t = NamedTempFile::new()?;
                 let mut file = t.as_file().try_clone()?;
-                io::copy(&mut entry, &mut file)?;
+                io::copy(&mut Limited::declared(BufReader::new(entry), name, size), &mut file)?;
                 *tmp = Some(t);
                 Ok(file)
             }
-            Self::ZipFile(archive) => {
-                let mut entry = archive.by_name(name).map_err(|e| io::Error::new(io::ErrorKind::NotFound, e))?;
+            Self::ZipFile(archive, _) => {
+                let entry = archive.by_name(name).map_err(|e| io::Error::new(io::ErrorKind::NotFound, e))?;
+                let size = entry.size();
                 let t = NamedTempFile::new()?;
                 let mut file = t.as_file().try_clone()?;
-                io::copy(&mut entry, &mut file)?;
+                io::copy(&mut Limited::declared(BufReader::new(entry), name, size), &mut file)?;
                 *tmp = Some(t);
                 Ok(file)
             }
@@ -76,151 +209,782 @@ impl<'a> DataSource<'a> {
             }
         }
     }
+
+    /// Opens `name` for reading with a reader of its own, which does not
+    /// borrow the archive, so that media can still be read while it is
+    /// open. ZIP entries are inflated as they are read, through a copy of
+    /// the archive.
+    ///
+    /// A ZIP entry is never read past the size the archive declares for it,
+    /// which is what the export size limit is checked against.
+    fn open_entry(&mut self, name: &str) -> io::Result<Box<dyn BufRead + 'a>> {
+        match self {
+            Self::ZipCursor(archive) => Self::open_zip_entry(archive.clone(), name),
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::ZipFile(_, path) => Self::open_zip_entry(ZipArchive::new(File::open(path)?)?, name),
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::Dir(path) => Ok(Box::new(BufReader::new(File::open(path.join(name))?))),
+        }
+    }
+
+    fn open_zip_entry<R: Read + Seek + 'a>(archive: ZipArchive<R>, name: &str) -> io::Result<Box<dyn BufRead + 'a>> {
+        let index = archive
+            .index_for_name(name)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{} is not in the archive", name)))?;
+        let entry = ArchiveEntryTryBuilder {
+            archive,
+            entry_builder: |archive: &mut ZipArchive<R>| -> io::Result<Box<dyn BufRead + '_>> {
+                let entry = archive.by_index(index)?;
+                let size = entry.size();
+                Ok(Box::new(Limited::declared(BufReader::new(entry), name, size)))
+            },
+            lifetime: PhantomData,
+        }
+        .try_build()?;
+        Ok(Box::new(entry))
+    }
+}
+
+/// One entry of an archive that the reader owns, read through
+/// `ZipArchive::by_index` so that its checksum is verified and data
+/// descriptors are handled.
+///
+/// The entry borrows the archive it is read from, so the two are kept
+/// together; `'a` is how long the archive's reader, which may borrow the
+/// caller's bytes, lives.
+#[self_referencing]
+struct ArchiveEntry<'a, R: 'a> {
+    archive: ZipArchive<R>,
+    #[borrows(mut archive)]
+    #[covariant]
+    entry: Box<dyn BufRead + 'this>,
+    lifetime: PhantomData<&'a ()>,
+}
+
+impl<R: Read + Seek> Read for ArchiveEntry<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.with_entry_mut(|entry| entry.read(buf))
+    }
+}
+
+impl<R: Read + Seek> BufRead for ArchiveEntry<'_, R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.with_entry_mut(|entry| entry.fill_buf())
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.with_entry_mut(|entry| entry.consume(amount));
+    }
+}
+
+/// Reads through `inner`, failing once more than `limit` bytes would come
+/// out of it, so that a size holds on what is actually read and not only
+/// on what an archive declares.
+struct Limited<R> {
+    inner: R,
+    remaining: u64,
+    limit: u64,
+    name: String,
+    overrun: fn(&str, u64) -> io::Error,
+}
+
+impl<R: BufRead> Limited<R> {
+    /// Fails with `ParseError::LimitExceeded` past a configured limit.
+    fn new(inner: R, name: &str, limit: u64) -> Self {
+        let overrun = |name: &str, limit| {
+            io::Error::other(ParseError::LimitExceeded(format!("{} holds more than {} bytes", name, limit)))
+        };
+        Self { inner, remaining: limit, limit, name: name.to_string(), overrun }
+    }
+
+    /// Fails as invalid data past the size a ZIP entry declares.
+    fn declared(inner: R, name: &str, size: u64) -> Self {
+        let overrun = |name: &str, size| {
+            let reason = format!("{} holds more than the {} bytes the archive declares", name, size);
+            io::Error::new(io::ErrorKind::InvalidData, reason)
+        };
+        Self { inner, remaining: size, limit: size, name: name.to_string(), overrun }
+    }
+}
+
+impl<R: BufRead> Read for Limited<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for Limited<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.remaining == 0 {
+            if self.inner.fill_buf()?.is_empty() {
+                return Ok(&[]);
+            }
+            return Err((self.overrun)(&self.name, self.limit));
+        }
+        let remaining = usize::try_from(self.remaining).unwrap_or(usize::MAX);
+        let available = self.inner.fill_buf()?;
+        Ok(&available[..available.len().min(remaining)])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.remaining -= amount as u64;
+        self.inner.consume(amount);
+    }
 }
 
 struct MediaEntry {
+    /// The full path of the entry within the export.
     name: String,
     size: u64,
 }
 
+/// The files of an export, by full entry path and by file name.
+///
+/// Re-packed exports (macOS "Compress", Google Drive downloads) put
+/// everything under a top-level folder, while the transcript names
+/// attachments by file name alone, so both keys resolve. When several
+/// folders hold a file of the same name, the one nearest the root wins,
+/// then the first by path.
+#[derive(Default)]
+struct MediaIndex {
+    entries: BTreeMap<String, MediaEntry>,
+    by_file_name: HashMap<String, Vec<String>>,
+}
+
+impl MediaIndex {
+    /// Adds an entry unless it is archiver metadata such as `__MACOSX/`
+    /// resource forks or `.DS_Store`.
+    fn insert(&mut self, name: String, size: u64) {
+        let file_name = Self::file_name(&name);
+        if name.starts_with("__MACOSX/") || file_name.starts_with("._") || file_name == ".DS_Store" {
+            return;
+        }
+        let paths = self.by_file_name.entry(file_name.to_lowercase()).or_default();
+        paths.push(name.clone());
+        paths.sort_by(|a, b| Self::depth(a).cmp(&Self::depth(b)).then_with(|| a.cmp(b)));
+        self.entries.insert(name.clone(), MediaEntry { name, size });
+    }
+
+    fn remove(&mut self, name: &str) {
+        if self.entries.remove(name).is_some()
+            && let Some(paths) = self.by_file_name.get_mut(&Self::file_name(name).to_lowercase())
+        {
+            paths.retain(|path| path != name);
+        }
+    }
+
+    /// Resolves a file name or path as written in the transcript.
+    fn get(&self, reference: &str) -> Option<&MediaEntry> {
+        if let Some(entry) = self.entries.get(reference) {
+            return Some(entry);
+        }
+        let paths = self.by_file_name.get(&Self::file_name(reference).to_lowercase())?;
+        let suffix = format!("/{}", reference.to_lowercase());
+        let path = paths.iter().find(|path| path.to_lowercase().ends_with(&suffix)).or(paths.first())?;
+        self.entries.get(path)
+    }
+
+    /// The size of every entry added together.
+    fn total_size(&self) -> u64 {
+        self.entries.values().map(|entry| entry.size).sum()
+    }
+
+    /// Every entry, in path order.
+    fn iter(&self) -> impl Iterator<Item = &MediaEntry> {
+        self.entries.values()
+    }
+
+    /// The entries that could be the chat transcript.
+    fn text_files(&self) -> Vec<String> {
+        self.entries.keys().filter(|name| name.to_lowercase().ends_with(".txt")).cloned().collect()
+    }
+
+    fn file_name(path: &str) -> &str {
+        path.rsplit('/').next().unwrap_or(path)
+    }
+
+    fn depth(path: &str) -> usize {
+        path.matches('/').count()
+    }
+}
+
+/// The senders of a transcript, learned from every header line before the
+/// transcript is split into messages.
+///
+/// A line such as `Dr. A: Clinic: hello` may come from `Dr. A` or from
+/// `Dr. A: Clinic`, so every prefix that ends before a `: ` is counted as a
+/// possible sender. The longest one heading more than half as many lines
+/// as the shortest wins: a name with a colon heads every line its owner
+/// sends, while a body that starts with `Re: ` only heads a few.
+///
+/// Prefixes are counted by hash rather than kept as text, since in a long
+/// chat most of them are the start of a message body. For the same reason
+/// the count is capped at `MAX_SENDER_CANDIDATES` prefixes: past that, the
+/// ones heading the fewest lines are dropped, while a real sender heads
+/// every line its owner sends and keeps its count.
+#[derive(Default)]
+struct Participants {
+    headed_lines: HashMap<u64, usize>,
+    hasher: RandomState,
+    /// Names that appear in recognised system events.
+    mentioned: HashSet<String>,
+}
+
+impl Participants {
+    /// Counts the possible senders of a header line, given the text after
+    /// its timestamp.
+    fn learn_line(&mut self, rest: &str) {
+        for candidate in Self::candidates(rest) {
+            *self.headed_lines.entry(self.hasher.hash_one(candidate)).or_default() += 1;
+        }
+        if self.headed_lines.len() > MAX_SENDER_CANDIDATES {
+            self.forget_rare_prefixes();
+        }
+    }
+
+    /// Drops the prefixes heading the fewest lines until at most half of
+    /// `MAX_SENDER_CANDIDATES` are left.
+    fn forget_rare_prefixes(&mut self) {
+        let mut fewest = 1;
+        while self.headed_lines.len() > MAX_SENDER_CANDIDATES / 2 {
+            self.headed_lines.retain(|_, count| *count > fewest);
+            fewest += 1;
+        }
+    }
+
+    fn learn_event(&mut self, event: SystemEvent) {
+        self.mentioned.extend(event.actors.into_iter().chain(event.subjects));
+    }
+
+    /// Drops the prefixes that head a single line, which make up most of
+    /// the map. Only learned lines are split afterwards, so a prefix that
+    /// is missing heads one.
+    fn forget_single_lines(&mut self) {
+        self.headed_lines.retain(|_, count| *count > 1);
+    }
+
+    /// Every prefix of `rest` that ends before a `: `, shortest first.
+    fn candidates(rest: &str) -> Vec<&str> {
+        rest.match_indices(": ").map(|(i, _)| &rest[..i]).filter(|name| !name.trim().is_empty()).collect()
+    }
+
+    fn count(&self, name: &str) -> usize {
+        self.headed_lines.get(&self.hasher.hash_one(name)).copied().unwrap_or(1)
+    }
+
+    /// Splits the text after a header's timestamp into sender and content,
+    /// or returns `None` if it names no sender.
+    fn split<'b>(&self, rest: &'b str) -> Option<(&'b str, &'b str)> {
+        let candidates = Self::candidates(rest);
+        let shortest = self.count(candidates.first()?);
+        let sender = candidates.iter().rev().find(|name| self.count(name) * 2 > shortest).unwrap_or(&candidates[0]);
+        Some((sender, &rest[sender.len() + 2..]))
+    }
+
+    /// Returns `true` if `sender` heads more than one line or takes part in
+    /// a system event.
+    fn is_known(&self, sender: &str) -> bool {
+        self.count(sender) > 1 || self.mentioned.contains(sender.trim_matches(|c: char| c.is_whitespace() || c == '\u{200e}'))
+    }
+}
+
+/// What the first pass over a transcript learns before any message is
+/// built.
+///
+/// The language is detected from the first header lines, which are kept
+/// until then; every later line is examined as it is read, so nothing grows
+/// with the transcript.
+struct Survey {
+    detector: DateFormatDetector,
+    locale: Locale,
+    /// Whether `locale` is still to be detected from `early_lines`.
+    detect_locale: bool,
+    early_lines: Vec<String>,
+    participants: Participants,
+    /// The latest header lines that may create or rename the group, up to
+    /// `MAX_SUBJECT_LINES`, oldest first.
+    subject_lines: Vec<String>,
+    blank: bool,
+    headed: bool,
+}
+
+impl Survey {
+    /// Starts a survey in `locale`, or in a language to be detected.
+    fn new(locale: Option<&Locale>) -> Self {
+        Self {
+            detector: DateFormatDetector::default(),
+            locale: locale.unwrap_or(Locale::english()).clone(),
+            detect_locale: locale.is_none(),
+            early_lines: Vec::new(),
+            participants: Participants::default(),
+            subject_lines: Vec::new(),
+            blank: true,
+            headed: false,
+        }
+    }
+
+    fn add_line(&mut self, line: String) {
+        self.blank &= line.trim().is_empty();
+        let Some((timestamp, rest)) = WhatsAppChatParser::split_timestamp(&line) else { return };
+        self.headed = true;
+        self.detector.add(&WhatsAppChatParser::normalise_timestamp(timestamp));
+        self.participants.learn_line(rest);
+        if !self.detect_locale {
+            Self::examine(&line, rest, &self.locale, &mut self.participants, &mut self.subject_lines);
+            return;
+        }
+        self.early_lines.push(line);
+        if self.early_lines.len() == locale::DETECTION_SAMPLE {
+            self.settle_locale();
+        }
+    }
+
+    /// Detects the language from the header lines read so far, if it is
+    /// still unknown, and examines those lines.
+    fn settle_locale(&mut self) {
+        if !self.detect_locale {
+            return;
+        }
+        let early_lines = std::mem::take(&mut self.early_lines);
+        let rests: Vec<&str> =
+            early_lines.iter().filter_map(|line| Some(WhatsAppChatParser::split_timestamp(line)?.1)).collect();
+        self.locale =
+            Locale::detect(rests.iter().map(|rest| rest.split_once(": ").map_or(*rest, |(_, content)| content))).clone();
+        self.detect_locale = false;
+        for (line, rest) in early_lines.iter().zip(rests) {
+            Self::examine(line, rest, &self.locale, &mut self.participants, &mut self.subject_lines);
+        }
+    }
+
+    /// Learns who a system event without a sender mentions, and keeps any
+    /// line that may name the group. Whether such a line is a system event
+    /// or a message quoting one is only known once every sender is.
+    fn examine(line: &str, rest: &str, locale: &Locale, participants: &mut Participants, subject_lines: &mut Vec<String>) {
+        if Participants::candidates(rest).is_empty()
+            && let Some(event) = SystemEvent::recognise(rest, locale)
+        {
+            participants.learn_event(event);
+        }
+        // Cleaning only changes text outside ASCII, and is slow enough to
+        // skip for every other line.
+        let names_group = |text: &str| {
+            if text.is_ascii() { locale.names_group(text) } else { locale.names_group(&text_encoding::clean_text(text)) }
+        };
+        let content = rest.split_once(": \u{200e}").map(|(_, content)| content);
+        if [Some(rest), content].into_iter().flatten().any(names_group) {
+            if subject_lines.len() == MAX_SUBJECT_LINES {
+                subject_lines.remove(0);
+            }
+            subject_lines.push(line.to_string());
+        }
+    }
+}
+
+/// Everything that stays the same for every message of one transcript.
+struct ParseContext {
+    date_format: DateFormat,
+    time_zone: ChatTimeZone,
+    locale: Locale,
+    media_files: MediaIndex,
+    /// Strip bidi controls and apply NFC to senders and text.
+    clean_text: bool,
+    /// Open media files to read their dimensions, durations and cards.
+    probe_media: bool,
+    self_name: Option<String>,
+}
+
+impl ParseContext {
+    fn clean(&self, text: &str) -> String {
+        if self.clean_text { text_encoding::clean_text(text) } else { text.to_string() }
+    }
+}
+
+/// A message whose header has been read while its continuation lines are
+/// still being collected.
+struct PendingMessage {
+    timestamp: String,
+    sender: Option<String>,
+    content: String,
+    line_number: usize,
+    continuation: Vec<String>,
+}
+
 /// The core logic for extracting and analyzing WhatsApp export files.
 pub struct WhatsAppChatParser;
 
 impl WhatsAppChatParser {
-    const DATE_PATTERN: &'static str = "%d/%m/%Y, %I:%M %p";
-
     /// Parses a WhatsApp ZIP export from a byte slice.
     /// This is used primarily for Web (WASM) environments.
-    pub fn parse_bytes(zip_bytes: &[u8]) -> Result<WhatsAppExport, Box<dyn std::error::Error>> {
-        let chat_name = "WhatsApp Chat".to_string();
-        let cursor = io::Cursor::new(zip_bytes);
-        let mut archive = ZipArchive::new(cursor)?;
-        
-        let mut media_files = HashMap::new();
-        let mut transcript_entry_name = None;
-
-        for i in 0..archive.len() {
-            let entry = archive.by_index(i)?;
-            if entry.is_dir() { continue; }
-            let name = entry.name().to_string();
-            if name.ends_with(".txt") {
-                transcript_entry_name = Some(name.clone());
-            } else {
-                media_files.insert(
-                    name.clone(),
-                    MediaEntry { name: name.clone(), size: entry.size() },
-                );
-            }
-        }
-
-        let mut source = DataSource::ZipCursor(archive);
-        let mut messages = Vec::new();
-        if let Some(txt_name) = transcript_entry_name {
-            let transcript = match &mut source {
-                DataSource::ZipCursor(archive) => {
-                    let mut entry = archive.by_name(&txt_name)?;
-                    let mut s = String::new();
-                    entry.read_to_string(&mut s)?;
-                    s
-                }
-                #[cfg(not(target_arch = "wasm32"))]
-                _ => unreachable!(),
-            };
-            let reader = BufReader::new(transcript.as_bytes());
-            messages = Self::parse_transcript(reader, &media_files, &mut source)?;
-        }
+    pub fn parse_bytes(zip_bytes: &[u8]) -> Result<WhatsAppExport, ParseError> {
+        Self::parse_bytes_with(zip_bytes, &ParseOptions::default())
+    }
 
-        Ok(WhatsAppExport::new(chat_name, messages))
+    /// Same as `parse_bytes`, configured by `options`.
+    pub fn parse_bytes_with(zip_bytes: &[u8], options: &ParseOptions) -> Result<WhatsAppExport, ParseError> {
+        Self::build_export(Self::messages_from_bytes(zip_bytes, options)?)
     }
 
     /// Parses a WhatsApp export from a local file path.
     /// Supports both directories and .zip files.
     #[cfg(not(target_arch = "wasm32"))]
-    pub fn parse<P: AsRef<Path>>(path: P) -> Result<WhatsAppExport, Box<dyn std::error::Error>> {
+    pub fn parse<P: AsRef<Path>>(path: P) -> Result<WhatsAppExport, ParseError> {
+        Self::parse_with(path, &ParseOptions::default())
+    }
+
+    /// Same as `parse`, configured by `options`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn parse_with<P: AsRef<Path>>(path: P, options: &ParseOptions) -> Result<WhatsAppExport, ParseError> {
+        Self::build_export(Self::messages(path, options)?)
+    }
+
+    /// Reads the messages of a ZIP export held in memory one at a time, as
+    /// `messages` does for a file.
+    pub fn messages_from_bytes<'a>(zip_bytes: &'a [u8], options: &ParseOptions) -> Result<MessageStream<'a>, ParseError> {
+        Self::messages_from_zip(ZipBytes::Borrowed(zip_bytes), options)
+    }
+
+    /// Same as `messages_from_bytes`, but the stream takes the bytes over,
+    /// so it can outlive the caller's buffer.
+    pub fn messages_from_vec(zip_bytes: Vec<u8>, options: &ParseOptions) -> Result<MessageStream<'static>, ParseError> {
+        Self::messages_from_zip(ZipBytes::Owned(Arc::new(zip_bytes)), options)
+    }
+
+    fn messages_from_zip<'a>(zip_bytes: ZipBytes<'a>, options: &ParseOptions) -> Result<MessageStream<'a>, ParseError> {
+        let mut archive = ZipArchive::new(io::Cursor::new(zip_bytes))?;
+        let media_files = Self::index_archive(&mut archive)?;
+        Self::open_stream(None, DataSource::ZipCursor(archive), media_files, options)
+    }
+
+    /// Reads the messages of an export one at a time instead of parsing it
+    /// as a whole. The transcript is decoded as it is read and attachments
+    /// are opened as their messages come up, so memory use does not grow
+    /// with the length of the chat. Supports both directories and .zip
+    /// files.
+    ///
+    /// The transcript is read twice: once up front to detect its layout
+    /// and learn its participants, then once more as messages are taken.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn messages<P: AsRef<Path>>(path: P, options: &ParseOptions) -> Result<MessageStream<'static>, ParseError> {
         let p = path.as_ref();
+        let source_name = Self::file_name(p);
         if p.is_dir() {
-            let chat_name = Self::extract_chat_name(p);
-            let mut media_files = HashMap::new();
-            let mut transcript_entry_name = None;
-            for entry in std::fs::read_dir(p)? {
-                let entry = entry?;
-                let name = entry.file_name().to_string_lossy().to_string();
-                if name.ends_with(".txt") {
-                    transcript_entry_name = Some(name.clone());
-                } else if entry.file_type()?.is_file() {
-                    media_files.insert(
-                        name.clone(),
-                        MediaEntry {
-                            name: name.clone(),
-                            size: entry.metadata()?.len(),
-                        },
-                    );
-                }
-            }
-            let mut source = DataSource::Dir(p.to_path_buf());
-            let mut messages = Vec::new();
-            if let Some(txt_name) = transcript_entry_name {
-                let transcript = std::fs::read_to_string(p.join(&txt_name))?;
-                let reader = BufReader::new(transcript.as_bytes());
-                messages = Self::parse_transcript(reader, &media_files, &mut source)?;
-            }
-            return Ok(WhatsAppExport::new(chat_name, messages));
+            let mut media_files = MediaIndex::default();
+            Self::index_dir(p, "", &mut media_files)?;
+            Self::open_stream(source_name, DataSource::Dir(p.to_path_buf()), media_files, options)
         } else {
-            let chat_name = Self::extract_chat_name(p);
-            let file = File::open(p)?;
-            let mut archive = ZipArchive::new(file)?;
-            let mut media_files = HashMap::new();
-            let mut transcript_entry_name = None;
-            for i in 0..archive.len() {
-                let entry = archive.by_index(i)?;
-                let name = entry.name().to_string();
-                if name.ends_with(".txt") { transcript_entry_name = Some(name); }
-                else { media_files.insert(name.clone(), MediaEntry { name, size: entry.size() }); }
-            }
-            let mut source = DataSource::ZipFile(archive);
-            let mut messages = Vec::new();
-            if let Some(txt_name) = transcript_entry_name {
-                let mut transcript = String::new();
-                if let DataSource::ZipFile(ref mut a) = source {
-                    a.by_name(&txt_name)?.read_to_string(&mut transcript)?;
-                }
-                let reader = BufReader::new(transcript.as_bytes());
-                messages = Self::parse_transcript(reader, &media_files, &mut source)?;
+            let mut archive = ZipArchive::new(File::open(p)?)?;
+            let media_files = Self::index_archive(&mut archive)?;
+            Self::open_stream(source_name, DataSource::ZipFile(archive, p.to_path_buf()), media_files, options)
+        }
+    }
+
+    /// Adds every file of a ZIP archive to a new index.
+    fn index_archive<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<MediaIndex, ParseError> {
+        let mut media_files = MediaIndex::default();
+        for i in 0..archive.len() {
+            let entry = archive.by_index(i)?;
+            if entry.is_dir() { continue; }
+            media_files.insert(entry.name().to_string(), entry.size());
+        }
+        Ok(media_files)
+    }
+
+    /// Adds every file below `dir` to `media_files` under its path relative
+    /// to the export root, with `/` separators as in a ZIP archive.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn index_dir(dir: &Path, prefix: &str, media_files: &mut MediaIndex) -> io::Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                Self::index_dir(&entry.path(), &format!("{}/", name), media_files)?;
+            } else if file_type.is_file() {
+                media_files.insert(name, entry.metadata()?.len());
             }
-            Ok(WhatsAppExport::new(chat_name, messages))
         }
+        Ok(())
     }
 
-    fn parse_transcript<R: BufRead>(
-        reader: R,
-        media_files: &HashMap<String, MediaEntry>,
+    /// Picks the chat transcript among the `.txt` files of an export and
+    /// removes it from `media_files`; the others stay there as documents.
+    ///
+    /// Candidates are tried in order of name, `_chat.txt` first, then names
+    /// mentioning WhatsApp such as `WhatsApp Chat with Bob.txt`, then the
+    /// rest, shallower paths first within each group. The first one whose
+    /// opening lines contain message headers wins. If none does, a
+    /// candidate with a WhatsApp name is still used.
+    ///
+    /// The export as a whole and each candidate are checked against the
+    /// size limits in `options` before anything is read; the transcript is
+    /// checked again as it is read.
+    fn find_transcript(
         source: &mut DataSource,
-    ) -> Result<Vec<WhatsAppMessage>, Box<dyn std::error::Error>> {
-        let pattern = Regex::new(
-            r"(?i)^(\d{1,2}/\d{1,2}/\d{4},[\s\u{202f}\u{00a0}]+\d{1,2}:\d{2}(?::\d{2})?[\s\u{202f}\u{00a0}]*[ap]m)\s*-\s*([^:]+):\s(.*)$",
-        )?;
-        let mut messages = Vec::new();
-        let mut pending = String::new();
-        for line_result in reader.lines() {
-            let line = line_result?;
-            if pattern.is_match(&line) {
-                if !pending.is_empty() {
-                    if let Some(msg) = Self::build_message(&pending, media_files, source) {
-                        messages.push(msg);
-                    }
-                }
-                pending = line;
-            } else if !pending.is_empty() {
-                pending.push('\n');
-                pending.push_str(&line);
+        media_files: &mut MediaIndex,
+        options: &ParseOptions,
+    ) -> Result<String, ParseError> {
+        if let Some(limit) = options.get_max_export_size() {
+            let size = media_files.total_size();
+            if size > limit {
+                return Err(ParseError::LimitExceeded(format!("the export holds {} bytes, more than {}", size, limit)));
             }
         }
-        if !pending.is_empty() {
-            if let Some(msg) = Self::build_message(&pending, media_files, source) {
-                messages.push(msg);
+        let mut candidates = media_files.text_files();
+        let rank = |name: &str| {
+            let base = MediaIndex::file_name(name).to_lowercase();
+            if base == "_chat.txt" {
+                0
+            } else if base.contains("whatsapp") || Self::chat_name_from_file(name).is_some() {
+                1
+            } else {
+                2
+            }
+        };
+        candidates.sort_by(|a, b| {
+            rank(a).cmp(&rank(b)).then_with(|| MediaIndex::depth(a).cmp(&MediaIndex::depth(b))).then_with(|| a.cmp(b))
+        });
+        let mut fallback = None;
+        let mut oversized = None;
+        for name in candidates {
+            if let Some(limit) = options.get_max_transcript_size()
+                && let Some(entry) = media_files.get(&name)
+                && entry.size > limit
+            {
+                oversized.get_or_insert(ParseError::LimitExceeded(format!(
+                    "{} holds {} bytes, more than {}",
+                    name, entry.size, limit
+                )));
+                continue;
+            }
+            let mut sample = Vec::new();
+            source
+                .open_entry(&name)
+                .and_then(|entry| entry.take(TRANSCRIPT_SAMPLE_BYTES as u64).read_to_end(&mut sample))
+                .map_err(|e| Self::transcript_error(&name, e))?;
+            let text = TextEncoding::decode(&sample).text;
+            let headers = text
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .take(TRANSCRIPT_SAMPLE_LINES)
+                .filter(|line| Self::split_timestamp(line).is_some())
+                .count();
+            if headers > 0 {
+                media_files.remove(&name);
+                return Ok(name);
+            }
+            if fallback.is_none() && rank(&name) < 2 {
+                fallback = Some(name);
             }
         }
-        Ok(messages)
+        let name = fallback.ok_or(oversized.unwrap_or(ParseError::NoTranscript))?;
+        media_files.remove(&name);
+        Ok(name)
+    }
+
+    /// Rewrites a header timestamp with ASCII digits, an ASCII comma and a
+    /// trailing English AM/PM marker, dropping bidi marks, so that
+    /// `DateFormat` can read `١٢‏/٣‏/٢٠٢٤، ٩:٠١ م` or `2024/03/12 午後9:01`.
+    fn normalise_timestamp(raw: &str) -> String {
+        let mut text: String = raw
+            .chars()
+            .filter(|c| !matches!(c, '\u{200e}' | '\u{200f}'))
+            .map(|c| match c {
+                '،' => ',',
+                _ => DIGIT_ZEROS
+                    .iter()
+                    .find_map(|&zero| {
+                        let offset = (c as u32).checked_sub(zero as u32).filter(|o| *o < 10)?;
+                        char::from_digit(offset, 10)
+                    })
+                    .unwrap_or(c),
+            })
+            .collect();
+        if let Some((marker, ascii)) = MERIDIEM_MARKERS.iter().find(|(marker, _)| text.contains(marker)) {
+            text = format!("{} {}", text.replacen(marker, " ", 1).trim(), ascii);
+        }
+        text
+    }
+
+    /// Finds the transcript in `source` and reads it through once to get
+    /// ready to stream its messages: the date format and language are
+    /// detected unless `options` set them, the participants are learned,
+    /// lines with invalid data are reported and the chat is named. Text
+    /// without a single header line is rejected as an unsupported format.
+    /// `source_name` is the archive or folder name.
+    fn open_stream<'a>(
+        source_name: Option<String>,
+        mut source: DataSource<'a>,
+        mut media_files: MediaIndex,
+        options: &ParseOptions,
+    ) -> Result<MessageStream<'a>, ParseError> {
+        let transcript_name = Self::find_transcript(&mut source, &mut media_files, options)?;
+        let unreadable = |e: io::Error| Self::transcript_error(&transcript_name, e);
+        let limit = options.get_max_transcript_size().unwrap_or(u64::MAX);
+        let open = |source: &mut DataSource<'a>| {
+            let entry = source.open_entry(&transcript_name)?;
+            LineReader::new(Limited::new(entry, &transcript_name, limit))
+        };
+        let mut lines = open(&mut source).map_err(unreadable)?;
+        let mut survey = Survey::new(options.get_locale());
+        let mut diagnostics = Vec::new();
+        let mut line_number = 0;
+        while let Some((line, invalid)) = lines.next_line().map_err(unreadable)? {
+            line_number += 1;
+            if invalid {
+                diagnostics.push(ParseDiagnostic {
+                    line: line_number,
+                    kind: DiagnosticKind::InvalidEncoding as i32,
+                    message: format!("replaced bytes that are not valid {}", lines.encoding()),
+                });
+            }
+            survey.add_line(line);
+        }
+        if !survey.blank && !survey.headed {
+            return Err(ParseError::UnsupportedFormat);
+        }
+        survey.settle_locale();
+        let Survey { detector, locale, mut participants, subject_lines, .. } = survey;
+        participants.forget_single_lines();
+        let lines = open(&mut source).map_err(unreadable)?;
+        let context = ParseContext {
+            date_format: options.get_date_format().or_else(|| detector.finish()).unwrap_or_default(),
+            time_zone: options.get_time_zone(),
+            locale,
+            media_files,
+            clean_text: options.get_clean_text(),
+            probe_media: options.get_probe_media(),
+            self_name: options.get_self_name().map(str::to_string),
+        };
+        // The group's latest name, read the way the messages will be.
+        let subject = subject_lines.iter().rev().find_map(|line| {
+            let header = Self::read_header(line, &participants, &context, None)?;
+            let event = Self::system_event(&header, &context.clean(header.content.trim_start_matches('\u{200e}')), &context.locale)?;
+            let names_group = matches!(event.kind, SystemEventKind::GroupCreated | SystemEventKind::SubjectChanged);
+            (names_group && !event.target.is_empty()).then_some(event.target)
+        });
+        let chat_name = Self::resolve_chat_name(options.get_chat_name(), Some(&transcript_name), source_name.as_deref(), subject);
+        Ok(MessageStream {
+            lines: Some(lines),
+            source,
+            context,
+            participants,
+            chat_name,
+            transcript_name,
+            max_messages: options.get_max_messages(),
+            interpolate_timestamps: options.get_interpolate_timestamps(),
+            line_number: 0,
+            pending: None,
+            last_time: None,
+            undated: Vec::new(),
+            undated_overflow: false,
+            previous_time: None,
+            ready: VecDeque::new(),
+            taken: 0,
+            diagnostics,
+        })
+    }
+
+    /// Collects the messages of `stream` into a `WhatsAppExport`.
+    fn build_export(mut stream: MessageStream) -> Result<WhatsAppExport, ParseError> {
+        let messages = stream.by_ref().collect::<Result<Vec<_>, _>>()?;
+        let MessageStream { chat_name, context, diagnostics, .. } = stream;
+        Ok(WhatsAppExport::new(
+            chat_name,
+            messages,
+            context.date_format,
+            context.time_zone,
+            context.locale.code,
+            diagnostics,
+        ))
+    }
+
+    /// Reports a failure to read the transcript `name` as the transcript
+    /// being unreadable, unless a size limit was hit.
+    fn transcript_error(name: &str, error: io::Error) -> ParseError {
+        match ParseError::from(error) {
+            ParseError::Io(error) => ParseError::TranscriptUnreadable(format!("{}: {}", name, error)),
+            other => other,
+        }
+    }
+
+    /// Names the chat, preferring in turn the name the caller gave, the
+    /// name in an export-style transcript or archive name such as
+    /// `WhatsApp Chat with Priya.txt`, the group's latest `subject` from its
+    /// "created group" or "changed the subject" events, and the bare
+    /// archive or folder name.
+    fn resolve_chat_name(
+        chat_name: Option<&str>,
+        transcript_name: Option<&str>,
+        source_name: Option<&str>,
+        subject: Option<String>,
+    ) -> String {
+        if let Some(name) = chat_name.map(str::trim).filter(|name| !name.is_empty()) {
+            return name.to_string();
+        }
+        if let Some(name) = [transcript_name, source_name].into_iter().flatten().find_map(Self::chat_name_from_file) {
+            return name;
+        }
+        if let Some(subject) = subject {
+            return subject;
+        }
+        source_name
+            .map(|name| name.strip_suffix(".zip").unwrap_or(name).trim())
+            .filter(|name| !name.is_empty())
+            .unwrap_or(DEFAULT_CHAT_NAME)
+            .to_string()
+    }
+
+    /// Reads the chat's name from an export file name in any built-in
+    /// language.
+    fn chat_name_from_file(file_name: &str) -> Option<String> {
+        Locale::builtin().iter().find_map(|locale| locale.chat_name_from_file(file_name))
+    }
+
+    /// Reads `line` as the header of a new message. A line from a sender
+    /// heard from only once is taken as a pasted quote and left in the
+    /// message before it when it is timed earlier than that message, unless
+    /// it is a recognised system event whose names do not take in the
+    /// sender. Lines without a sender are treated the same way.
+    fn read_header<'a>(
+        line: &'a str,
+        participants: &Participants,
+        context: &ParseContext,
+        last_time: Option<NaiveDateTime>,
+    ) -> Option<Header<'a>> {
+        let (timestamp, rest) = Self::split_timestamp(line)?;
+        let in_order = || match (last_time, context.date_format.parse(&Self::normalise_timestamp(timestamp))) {
+            (Some(last), Some(time)) => time >= last,
+            _ => true,
+        };
+        let system = Header { timestamp, sender: None, content: rest };
+        if let Some((sender, content)) = participants.split(rest) {
+            let message = Header { timestamp, sender: Some(sender), content };
+            if participants.is_known(sender) {
+                return Some(message);
+            }
+            // A system event can quote text with a colon, as in a new
+            // subject, which makes it look like a message. When a name the
+            // event reads holds the colon instead, as in `Carol: I just
+            // added you`, the sender prefix is the better reading.
+            let event = SystemEvent::recognise(rest, &context.locale);
+            if event.is_some_and(|e| e.actors.iter().chain(&e.subjects).all(|name| !name.contains(": "))) {
+                return Some(system);
+            }
+            return in_order().then_some(message);
+        }
+        (SystemEvent::recognise(rest, &context.locale).is_some() || in_order()).then_some(system)
+    }
+
+    /// Splits a transcript line that looks like a message header in either
+    /// the Android or the iOS layout into its timestamp and the text after
+    /// it.
+    fn split_timestamp(line: &str) -> Option<(&str, &str)> {
+        let caps = ANDROID_HEADER.captures(line).or_else(|| IOS_HEADER.captures(line))?;
+        Some((caps.get(1)?.as_str(), caps.get(2)?.as_str()))
     }
 
     fn to_proto_timestamp(dt: DateTime<Utc>) -> prost_types::Timestamp {
@@ -230,34 +994,127 @@ impl WhatsAppChatParser {
         }
     }
 
+    /// Reads the event of a header with no sender, or of an iOS header
+    /// whose `content` is marked as a system event. iOS writes system
+    /// events under the chat name with a leading U+200E mark, so they look
+    /// like an ordinary sender line.
+    fn system_event(header: &Header, content: &str, locale: &Locale) -> Option<SystemEvent> {
+        match header.sender {
+            None => Some(SystemEvent::parse(content, locale)),
+            Some(_) if header.content.starts_with('\u{200e}') => SystemEvent::recognise(content, locale),
+            Some(_) => None,
+        }
+    }
+
     fn build_message(
-        raw_line: &str,
-        media_files: &HashMap<String, MediaEntry>,
+        header: &Header,
+        continuation: &[String],
+        line_number: usize,
+        context: &ParseContext,
         source: &mut DataSource,
+        diagnostics: &mut Vec<ParseDiagnostic>,
     ) -> Option<WhatsAppMessage> {
-        let first_line = raw_line.lines().next()?;
-        let pattern = Regex::new(
-            r"(?i)^(\d{1,2}/\d{1,2}/\d{4},[\s\u{202f}\u{00a0}]+\d{1,2}:\d{2}(?::\d{2})?[\s\u{202f}\u{00a0}]*[ap]m)\s*-\s*([^:]+):\s(.*)$",
-        ).ok()?;
-        let caps = pattern.captures(first_line)?;
-        let timestamp_str = caps.get(1)?.as_str();
-        let sender = caps.get(2)?.as_str().trim().to_string();
-        let content = caps.get(3)?.as_str().to_string();
-        let timestamp = Self::parse_timestamp(timestamp_str);
-        let msg_type = Self::classify_message(&content);
+        let ParseContext { date_format, time_zone, locale, media_files, probe_media, self_name, .. } = context;
+        let clean = |text: &str| context.clean(text);
+        let content = clean(header.content.trim_start_matches('\u{200e}'));
+        let system_event = Self::system_event(header, &content, locale);
+        let raw_sender = header.sender.filter(|_| system_event.is_none()).map(str::trim).unwrap_or_default();
+        let sender = clean(raw_sender);
+        let from_self = !sender.is_empty() && self_name.as_ref().is_some_and(|name| clean(name.trim()) == sender);
+        let raw_sender = if raw_sender != sender { raw_sender.to_string() } else { String::new() };
+        let mut raw_body = header.content.trim_start_matches('\u{200e}').to_string();
+        let mut body = content.clone();
+        for line in continuation {
+            raw_body.push('\n');
+            raw_body.push_str(line);
+            body.push('\n');
+            body.push_str(&clean(line));
+        }
+        let raw_text = if raw_body != body { raw_body } else { String::new() };
+        let (body, edited) = locale.strip_edited(&body);
+        let deleted = locale.is_deleted(&body);
+        // Media captions follow the attachment line.
+        let caption = body.split_once('\n').map(|(_, rest)| rest.trim().to_string()).unwrap_or_default();
+        // The iOS placeholders are recognised by the mark before them, which
+        // cleaning removes.
+        let omitted_type = if system_event.is_none() { locale.omitted_media_type(header.content) } else { None };
+        let media_omitted = omitted_type.is_some();
+        // Absent attachments get no made-up file name.
+        let fallback_name = |name: &str| if media_omitted { String::new() } else { name.to_string() };
+        let timestamp = Self::parse_timestamp(&Self::normalise_timestamp(header.timestamp), date_format, time_zone);
+        if timestamp.is_none() {
+            diagnostics.push(ParseDiagnostic {
+                line: line_number as u32,
+                kind: DiagnosticKind::UnparsedTimestamp as i32,
+                message: format!("could not read \"{}\" as {}", header.timestamp, date_format),
+            });
+        }
+        let location = if system_event.is_some() { None } else { Self::parse_location(&body, locale) };
+        let poll = if system_event.is_some() { None } else { Self::parse_poll(&body, locale) };
+        let call = if system_event.is_some() { None } else { Self::parse_call(&content, locale) };
+        let msg_type = if system_event.is_some() {
+            Type::System
+        } else if location.is_some() {
+            Type::Location
+        } else if poll.is_some() {
+            Type::Poll
+        } else if call.is_some() {
+            Type::Call
+        } else if let Some(media_type) = omitted_type {
+            media_type
+        } else {
+            Self::classify_message(&content, locale)
+        };
         let base = Some(MessageBase {
             sender,
-            timestamp: Some(Self::to_proto_timestamp(timestamp)),
+            timestamp: timestamp.map(|(instant, _)| Self::to_proto_timestamp(instant)),
             r#type: msg_type as i32,
+            raw_timestamp: header.timestamp.to_string(),
+            timestamp_status: if timestamp.is_some() { TimestampStatus::Exact } else { TimestampStatus::Unknown } as i32,
+            utc_offset_seconds: timestamp.map_or(0, |(_, offset)| offset),
+            deleted,
+            edited,
+            media_omitted,
+            from_self,
+            raw_sender,
+            raw_text,
         });
 
         match msg_type {
+            Type::System => {
+                let event = system_event?;
+                Some(WhatsAppMessage {
+                    content: Some(whatsapp_message::Content::System(SystemMessage {
+                        base,
+                        text: content,
+                        kind: event.kind as i32,
+                        actors: event.actors,
+                        target: event.target,
+                        subjects: event.subjects,
+                    })),
+                })
+            }
+            Type::Location => Some(WhatsAppMessage {
+                content: Some(whatsapp_message::Content::Location(LocationMessage { base, ..location? })),
+            }),
+            Type::MediaOmitted => Some(WhatsAppMessage {
+                content: Some(whatsapp_message::Content::OmittedMedia(OmittedMediaMessage { base })),
+            }),
+            Type::Call => Some(WhatsAppMessage {
+                content: Some(whatsapp_message::Content::Call(CallMessage { base, ..call? })),
+            }),
+            Type::Poll => Some(WhatsAppMessage {
+                content: Some(whatsapp_message::Content::Poll(PollMessage { base, ..poll? })),
+            }),
             Type::Text => Some(WhatsAppMessage {
-                content: Some(whatsapp_message::Content::Text(TextMessage { base, text: content })),
+                content: Some(whatsapp_message::Content::Text(TextMessage {
+                    base,
+                    text: if deleted { String::new() } else { body },
+                })),
             }),
             Type::Image => {
-                let info = Self::find_media(&content, media_files, "image");
-                let name = info.as_ref().map(|i| i.name.clone()).unwrap_or_else(|| "image.jpg".to_string());
+                let info = Self::find_media(&content, media_files, "image", locale);
+                let name = info.as_ref().map(|i| i.name.clone()).unwrap_or_else(|| fallback_name("image.jpg"));
                 let size = info.as_ref().map(|i| i.size).unwrap_or(0);
                 let width;
                 let height;
@@ -265,13 +1122,12 @@ impl WhatsAppChatParser {
                 {
                     let mut w = 0;
                     let mut h = 0;
-                    if let Some(entry_info) = info {
-                        if let Ok(buffer) = source.read_to_vec(&entry_info.name) {
-                            if let Ok(img) = image::load_from_memory(&buffer) {
-                                w = img.width();
-                                h = img.height();
-                            }
-                        }
+                    if let Some(entry_info) = info.filter(|_| *probe_media)
+                        && let Ok(buffer) = source.read_to_vec(&entry_info.name)
+                        && let Ok(img) = image::load_from_memory(&buffer)
+                    {
+                        w = img.width();
+                        h = img.height();
                     }
                     width = w;
                     height = h;
@@ -285,13 +1141,13 @@ impl WhatsAppChatParser {
                 let extension = Self::extension(&name);
                 Some(WhatsAppMessage {
                     content: Some(whatsapp_message::Content::Image(ImageMessage {
-                        base, name, height, width, size, extension,
+                        base, name, height, width, size, extension, caption,
                     })),
                 })
             }
             Type::Video => {
-                let info = Self::find_media(&content, media_files, "video");
-                let name = info.as_ref().map(|i| i.name.clone()).unwrap_or_else(|| "video.mp4".to_string());
+                let info = Self::find_media(&content, media_files, "video", locale);
+                let name = info.as_ref().map(|i| i.name.clone()).unwrap_or_else(|| fallback_name("video.mp4"));
                 let size = info.as_ref().map(|i| i.size).unwrap_or(0);
                 let width;
                 let height;
@@ -300,7 +1156,7 @@ impl WhatsAppChatParser {
                 {
                     let mut w = 0;
                     let mut h = 0;
-                    if let Some(entry_info) = info {
+                    if let Some(entry_info) = info.filter(|_| *probe_media) {
                         let mut tmp = None;
                         if let Ok(file_handle) = source.get_file_handle(&entry_info.name, &mut tmp) {
                             duration = Self::parse_mp4_duration(&file_handle);
@@ -321,18 +1177,18 @@ impl WhatsAppChatParser {
                 let extension = Self::extension(&name);
                 Some(WhatsAppMessage {
                     content: Some(whatsapp_message::Content::Video(VideoMessage {
-                        base, name, size, duration, extension, width, height,
+                        base, name, size, duration, extension, width, height, caption,
                     })),
                 })
             }
             Type::Audio => {
-                let info = Self::find_media(&content, media_files, "audio");
-                let name = info.as_ref().map(|i| i.name.clone()).unwrap_or_else(|| "audio.opus".to_string());
+                let info = Self::find_media(&content, media_files, "audio", locale);
+                let name = info.as_ref().map(|i| i.name.clone()).unwrap_or_else(|| fallback_name("audio.opus"));
                 let size = info.as_ref().map(|i| i.size).unwrap_or(0);
                 let mut duration = "0:00".to_string();
                 #[cfg(not(target_arch = "wasm32"))]
                 {
-                    if let Some(entry_info) = info {
+                    if let Some(entry_info) = info.filter(|_| *probe_media) {
                         let ext = Self::extension(&name);
                         let mut tmp = None;
                         if let Ok(file_handle) = source.get_file_handle(&entry_info.name, &mut tmp) {
@@ -356,22 +1212,39 @@ impl WhatsAppChatParser {
                 })
             }
             Type::Document => {
-                let info = Self::find_media(&content, media_files, "document");
+                let info = Self::find_media(&content, media_files, "document", locale);
                 let name = if let Some(i) = info { i.name.clone() } else {
-                    if let Some(pos) = content.find(" (file attached)") { content[..pos].trim().to_string() }
-                    else { "document.pdf".to_string() }
+                    locale.attachment_name(&content).unwrap_or_else(|| fallback_name("document.pdf"))
                 };
                 let size = info.as_ref().map(|i| i.size).unwrap_or(0);
                 let extension = Self::extension(&name);
                 Some(WhatsAppMessage {
                     content: Some(whatsapp_message::Content::Document(DocumentMessage {
-                        base, name, extension, size,
+                        base, name, extension, size, caption,
+                    })),
+                })
+            }
+            Type::Contact => {
+                let info = Self::find_media(&content, media_files, "contact", locale);
+                let name = match info {
+                    Some(i) => i.name.clone(),
+                    None => locale.attachment_name(&content).unwrap_or_else(|| fallback_name("contact.vcf")),
+                };
+                let size = info.as_ref().map(|i| i.size).unwrap_or(0);
+                let contacts = info
+                    .filter(|_| *probe_media)
+                    .and_then(|i| source.read_to_vec(&i.name).ok())
+                    .map(|data| vcard::parse_vcards(&String::from_utf8_lossy(&data)))
+                    .unwrap_or_default();
+                Some(WhatsAppMessage {
+                    content: Some(whatsapp_message::Content::Contact(ContactMessage {
+                        base, name, size, contacts,
                     })),
                 })
             }
             Type::Sticker => {
-                let info = Self::find_media(&content, media_files, "sticker");
-                let name = info.as_ref().map(|i| i.name.clone()).unwrap_or_else(|| "sticker.webp".to_string());
+                let info = Self::find_media(&content, media_files, "sticker", locale);
+                let name = info.as_ref().map(|i| i.name.clone()).unwrap_or_else(|| fallback_name("sticker.webp"));
                 let size = info.as_ref().map(|i| i.size).unwrap_or(0);
                 let extension = Self::extension(&name);
                 Some(WhatsAppMessage {
@@ -383,11 +1256,12 @@ impl WhatsAppChatParser {
         }
     }
 
-    fn classify_message(content: &str) -> Type {
+    fn classify_message(content: &str, locale: &Locale) -> Type {
         let lc = content.to_lowercase().trim().to_string();
-        if lc == "<media omitted>" { return Type::Audio; }
-        if lc.contains("this message was deleted") { return Type::Text; }
-        if lc.contains("(file attached)") || lc.starts_with("doc-") || lc.starts_with("img-") || lc.starts_with("vid-") || lc.starts_with("ptt-") {
+        if let Some(media_type) = locale.omitted_media_type(&lc) { return media_type; }
+        if locale.is_deleted(&lc) { return Type::Text; }
+        if locale.has_attachment(&lc) || lc.starts_with("doc-") || lc.starts_with("img-") || lc.starts_with("vid-") || lc.starts_with("ptt-") {
+            if lc.contains(".vcf") { return Type::Contact; }
             if lc.contains(".webp") { return Type::Sticker; }
             if [".jpg", ".jpeg", ".png", ".gif", ".bmp", ".webp"].iter().any(|&ext| lc.contains(ext)) { return Type::Image; }
             if [".mp4", ".avi", ".mov", ".mkv", ".webm"].iter().any(|&ext| lc.contains(ext)) { return Type::Video; }
@@ -397,21 +1271,139 @@ impl WhatsAppChatParser {
         Type::Text
     }
 
-    fn find_media<'a>(content: &str, media_files: &'a HashMap<String, MediaEntry>, media_type: &str) -> Option<&'a MediaEntry> {
-        let lc = content.to_lowercase();
-        for (name, entry) in media_files {
+    /// Reads a call log entry. iOS follows the call with its length or with
+    /// `Tap to call back` / `No answer` when it was not picked up.
+    fn parse_call(content: &str, locale: &Locale) -> Option<CallMessage> {
+        let (call, detail) = locale.call_phrase(content)?;
+        let mut seconds = 0;
+        for part in CALL_DURATION.captures_iter(&detail) {
+            let value: u64 = part[1].parse().unwrap_or(0);
+            seconds += match part[2].chars().next() {
+                Some('h') => value * 3600,
+                Some('m') => value * 60,
+                _ => value,
+            };
+        }
+        let answered = seconds > 0;
+        Some(CallMessage {
+            base: None,
+            video: call.video,
+            missed: call.missed || (!answered && !detail.is_empty()),
+            duration: if answered { format!("{}:{:02}", seconds / 60, seconds % 60) } else { String::new() },
+            group: call.group,
+        })
+    }
+
+    /// Reads a shared pin or live location. A pin is a maps link on the last
+    /// line, optionally prefixed with `location:` and preceded by the place
+    /// name and address lines. Returns `None` for anything else, including
+    /// text that merely contains a maps link.
+    fn parse_location(body: &str, locale: &Locale) -> Option<LocationMessage> {
+        let lines: Vec<&str> = body
+            .lines()
+            .map(|l| l.trim_matches(|c: char| c.is_whitespace() || c == '\u{200e}'))
+            .filter(|l| !l.is_empty())
+            .collect();
+        let first = lines.first()?.to_lowercase();
+        let live = locale.live_location.iter().any(|m| first.starts_with(m.as_str()));
+        let mut location = LocationMessage { live, ..Default::default() };
+
+        let last = lines.last()?;
+        let url = locale
+            .location_prefixes
+            .iter()
+            .find_map(|p| locale::strip_prefix_ignore_case(last, p))
+            .map(str::trim)
+            .unwrap_or(last);
+        if MAPS_URL.is_match(url) {
+            location.url = url.to_string();
+            if let Some(caps) = COORDINATES.captures(url) {
+                location.latitude = caps[1].parse().unwrap_or_default();
+                location.longitude = caps[2].parse().unwrap_or_default();
+            }
+            if !live && lines.len() > 1 {
+                location.name = lines[0].to_string();
+                location.address_lines = lines[1..lines.len() - 1].iter().map(|l| l.to_string()).collect();
+            }
+        } else if !live {
+            return None;
+        }
+        Some(location)
+    }
+
+    /// Reads a poll: a `POLL:` line, the question (on the same line or the
+    /// next), then one `OPTION: text (n votes)` line per option.
+    fn parse_poll(body: &str, locale: &Locale) -> Option<PollMessage> {
+        let strip = |line: &str, prefixes: &[String]| -> Option<String> {
+            prefixes
+                .iter()
+                .find_map(|p| locale::strip_prefix_ignore_case(line, p))
+                .map(|rest| rest.trim().to_string())
+        };
+        let mut lines = body
+            .lines()
+            .map(|l| l.trim_matches(|c: char| c.is_whitespace() || c == '\u{200e}'))
+            .filter(|l| !l.is_empty());
+        let mut question = strip(lines.next()?, &locale.poll_prefixes)?;
+        let mut poll = PollMessage::default();
+        for line in lines {
+            if let Some(option) = strip(line, &locale.poll_option_prefixes) {
+                let (text, votes) = match POLL_TALLY.captures(&option) {
+                    Some(caps) => (caps[1].to_string(), caps[2].parse().unwrap_or(0)),
+                    None => (option, 0),
+                };
+                poll.options.push(PollOption { text, votes });
+            } else if locale.poll_multi_select.iter().any(|m| m == line.to_lowercase().trim_matches(['(', ')'])) {
+                poll.multi_select = true;
+            } else if poll.options.is_empty() {
+                if !question.is_empty() {
+                    question.push('\n');
+                }
+                question.push_str(line);
+            }
+        }
+        if poll.options.is_empty() {
+            return None;
+        }
+        poll.question = question;
+        Some(poll)
+    }
+
+    /// Finds the file an attachment line refers to, first by the name the
+    /// line gives and then by any indexed file name the line mentions.
+    /// Among several mentioned files the one named by full path wins, then
+    /// the one nearest the root, then the first by path.
+    fn find_media<'a>(content: &str, media_files: &'a MediaIndex, media_type: &str, locale: &Locale) -> Option<&'a MediaEntry> {
+        let matches_type = |name: &str| {
             let ln = name.to_lowercase();
-            let matches_type = match media_type {
+            match media_type {
                 "sticker" => ln.ends_with(".webp"),
+                "contact" => ln.ends_with(".vcf"),
                 "image" => [".jpg", ".jpeg", ".png", ".gif", ".bmp", ".webp"].iter().any(|&ext| ln.ends_with(ext)),
                 "video" => [".mp4", ".avi", ".mov", ".mkv", ".webm"].iter().any(|&ext| ln.ends_with(ext)),
                 "audio" => [".mp3", ".wav", ".ogg", ".m4a", ".aac", ".opus"].iter().any(|&ext| ln.ends_with(ext)),
                 "document" => true,
                 _ => false,
-            };
-            if matches_type && (lc.contains(name) || lc.contains(&ln)) { return Some(entry); }
+            }
+        };
+        if let Some(reference) = locale.attachment_name(content)
+            && let Some(entry) = media_files.get(&reference)
+            && matches_type(&entry.name)
+        {
+            return Some(entry);
         }
-        None
+        let lc = content.to_lowercase();
+        media_files
+            .iter()
+            .filter(|entry| matches_type(&entry.name))
+            .filter_map(|entry| {
+                let ln = entry.name.to_lowercase();
+                let by_path = lc.contains(&ln);
+                (by_path || lc.contains(MediaIndex::file_name(&ln)))
+                    .then(|| ((!by_path, MediaIndex::depth(&entry.name)), entry))
+            })
+            .min_by_key(|(key, _)| *key)
+            .map(|(_, entry)| entry)
     }
 
     #[cfg(not(target_arch = "wasm32"))]
@@ -419,7 +1411,7 @@ impl WhatsAppChatParser {
         let mut reader = BufReader::new(file);
         let len = file.metadata().map(|m| m.len()).unwrap_or(0);
         if len == 0 { return "0:00".to_string(); }
-        let start = if len > 65536 { len - 65536 } else { 0 };
+        let start = len.saturating_sub(65536);
         let mut buffer = vec![0u8; (len - start) as usize];
         if reader.seek(SeekFrom::Start(start)).is_ok() && reader.read_exact(&mut buffer).is_ok() {
             for i in (0..(buffer.len() as isize - 4)).rev() {
@@ -448,7 +1440,7 @@ impl WhatsAppChatParser {
                     let version = buffer[i + 4] as usize;
                     let (ts, dur) = if version == 1 && i + 36 < bytes_read { (Self::i32(&buffer, i + 24), Self::i64(&buffer, i + 28)) } 
                     else if i + 24 < bytes_read { (Self::i32(&buffer, i + 16), Self::i32(&buffer, i + 20)) } else { continue };
-                    if ts > 0 { let s = dur / ts; return format!("{}:{:02}", s / 60, s % 60); }
+                    if let Some(s) = dur.checked_div(ts) { return format!("{}:{:02}", s / 60, s % 60); }
                 }
             }
             pos += bytes_read as u64 - 100;
@@ -488,10 +1480,337 @@ impl WhatsAppChatParser {
     fn i64(b: &[u8], o: usize) -> u64 { (Self::i32(b, o) << 32) | (Self::i32(b, o + 4) & 0xFFFFFFFF) }
     fn extension(f: &str) -> String { Path::new(f).extension().and_then(|ext| ext.to_str()).unwrap_or("-").to_lowercase() }
     #[cfg(not(target_arch = "wasm32"))]
-    fn extract_chat_name(path: &Path) -> String { path.file_name().and_then(|n| n.to_str()).unwrap_or("").replace(".zip", "").replace("WhatsApp Chat with ", "") }
-    fn parse_timestamp(raw: &str) -> DateTime<Utc> {
-        let clean = raw.split_whitespace().collect::<Vec<_>>().join(" ");
-        if let Ok(naive) = NaiveDateTime::parse_from_str(&clean, Self::DATE_PATTERN) { return DateTime::from_naive_utc_and_offset(naive, Utc); }
-        Utc::now()
+    fn file_name(path: &Path) -> Option<String> { path.file_name().map(|n| n.to_string_lossy().to_string()) }
+    fn parse_timestamp(raw: &str, date_format: &DateFormat, time_zone: &ChatTimeZone) -> Option<(DateTime<Utc>, i32)> {
+        time_zone.to_instant(date_format.parse(raw)?)
+    }
+}
+
+/// The messages of one export, read from the transcript one at a time by
+/// [`WhatsAppChatParser::messages`].
+///
+/// Each message is built, and its attachment probed, only when it is
+/// taken. A message whose time could not be read is held back until the
+/// next one with a time, so that it can be given a time between the two,
+/// unless `ParseOptions::interpolate_timestamps` is off. A run of more
+/// than `MAX_UNDATED` such messages is let through without times instead.
+/// Reading stops at the first error, which is returned in place of a
+/// message.
+pub struct MessageStream<'a> {
+    /// `None` once the transcript has been read to the end.
+    lines: Option<LineReader<'a>>,
+    source: DataSource<'a>,
+    context: ParseContext,
+    participants: Participants,
+    chat_name: String,
+    transcript_name: String,
+    max_messages: Option<usize>,
+    interpolate_timestamps: bool,
+    line_number: usize,
+    pending: Option<PendingMessage>,
+    last_time: Option<NaiveDateTime>,
+    /// Messages waiting for the next message with a time.
+    undated: Vec<WhatsAppMessage>,
+    /// Whether the messages without a time since the last one with a time
+    /// were too many to hold back.
+    undated_overflow: bool,
+    /// The time and UTC offset of the last message with a time.
+    previous_time: Option<(i64, i32)>,
+    ready: VecDeque<WhatsAppMessage>,
+    taken: usize,
+    diagnostics: Vec<ParseDiagnostic>,
+}
+
+impl MessageStream<'_> {
+    /// The chat's name, resolved as for `WhatsAppExport` before any message
+    /// is read.
+    pub fn get_chat_name(&self) -> &str {
+        &self.chat_name
+    }
+
+    pub fn get_date_format(&self) -> DateFormat {
+        self.context.date_format
+    }
+
+    pub fn get_time_zone(&self) -> ChatTimeZone {
+        self.context.time_zone
+    }
+
+    pub fn get_locale(&self) -> &Locale {
+        &self.context.locale
+    }
+
+    /// The entry name of the transcript within the export.
+    pub fn get_transcript_name(&self) -> &str {
+        &self.transcript_name
+    }
+
+    /// The problems found so far. Invalid data anywhere in the transcript
+    /// is reported from the start; timestamps that could not be read are
+    /// added as their messages are taken.
+    pub fn get_diagnostics(&self) -> &[ParseDiagnostic] {
+        &self.diagnostics
+    }
+
+    /// Reads `line` as the header of a new message, which completes the one
+    /// before it, or adds it to the message it continues.
+    fn read_line(&mut self, line: String) {
+        self.line_number += 1;
+        let header = WhatsAppChatParser::read_header(&line, &self.participants, &self.context, self.last_time).map(
+            |header| PendingMessage {
+                timestamp: header.timestamp.to_string(),
+                sender: header.sender.map(str::to_string),
+                content: header.content.to_string(),
+                line_number: self.line_number,
+                continuation: Vec::new(),
+            },
+        );
+        let Some(next) = header else {
+            if let Some(pending) = &mut self.pending {
+                pending.continuation.push(line);
+            }
+            return;
+        };
+        let normalised = WhatsAppChatParser::normalise_timestamp(&next.timestamp);
+        if let Some(time) = self.context.date_format.parse(&normalised) {
+            self.last_time = Some(time);
+        }
+        if let Some(pending) = self.pending.replace(next) {
+            self.complete(pending);
+        }
+    }
+
+    fn complete(&mut self, pending: PendingMessage) {
+        let header = Header {
+            timestamp: &pending.timestamp,
+            sender: pending.sender.as_deref(),
+            content: &pending.content,
+        };
+        if let Some(message) = WhatsAppChatParser::build_message(
+            &header,
+            &pending.continuation,
+            pending.line_number,
+            &self.context,
+            &mut self.source,
+            &mut self.diagnostics,
+        ) {
+            self.queue(message);
+        }
+    }
+
+    /// Queues a completed message. The messages held back before a message
+    /// with a time are given a time linearly interpolated between it and
+    /// the message with a time before them, or its own time when there is
+    /// none. Without interpolation nothing is held back, and once
+    /// `MAX_UNDATED` messages are, they and the rest of their run are let
+    /// through as they are.
+    fn queue(&mut self, message: WhatsAppMessage) {
+        let time = message.base().and_then(|base| Some((base.timestamp.as_ref()?.seconds, base.utc_offset_seconds)));
+        let Some((seconds, offset)) = time else {
+            if !self.interpolate_timestamps || self.undated_overflow {
+                self.ready.push_back(message);
+            } else {
+                self.undated.push(message);
+                if self.undated.len() == MAX_UNDATED {
+                    self.ready.extend(self.undated.drain(..));
+                    self.undated_overflow = true;
+                }
+            }
+            return;
+        };
+        self.undated_overflow = false;
+        let gap = self.undated.len() as i64 + 1;
+        for (i, mut undated) in std::mem::take(&mut self.undated).into_iter().enumerate() {
+            let (estimate, estimate_offset) = match self.previous_time {
+                Some((previous, previous_offset)) => {
+                    (previous + (seconds - previous) * (i as i64 + 1) / gap, previous_offset)
+                }
+                None => (seconds, offset),
+            };
+            Self::approximate(&mut undated, estimate, estimate_offset);
+            self.ready.push_back(undated);
+        }
+        self.previous_time = Some((seconds, offset));
+        self.ready.push_back(message);
+    }
+
+    /// Completes the last message at the end of the transcript. Messages
+    /// still held back copy the time of the last message with one.
+    fn finish(&mut self) {
+        if let Some(pending) = self.pending.take() {
+            self.complete(pending);
+        }
+        for mut undated in std::mem::take(&mut self.undated) {
+            if let Some((seconds, offset)) = self.previous_time {
+                Self::approximate(&mut undated, seconds, offset);
+            }
+            self.ready.push_back(undated);
+        }
+    }
+
+    fn approximate(message: &mut WhatsAppMessage, seconds: i64, offset: i32) {
+        if let Some(base) = message.base_mut() {
+            base.timestamp = Some(prost_types::Timestamp { seconds, nanos: 0 });
+            base.utc_offset_seconds = offset;
+            base.timestamp_status = TimestampStatus::Approximate as i32;
+        }
+    }
+
+    /// Stops reading after an error.
+    fn stop(&mut self) {
+        self.lines = None;
+        self.pending = None;
+        self.undated.clear();
+        self.ready.clear();
+    }
+}
+
+impl Iterator for MessageStream<'_> {
+    type Item = Result<WhatsAppMessage, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(message) = self.ready.pop_front() {
+                self.taken += 1;
+                if let Some(limit) = self.max_messages
+                    && self.taken > limit
+                {
+                    self.stop();
+                    let reason = format!("the transcript holds more than {} messages", limit);
+                    return Some(Err(ParseError::LimitExceeded(reason)));
+                }
+                return Some(Ok(message));
+            }
+            match self.lines.as_mut()?.next_line() {
+                Ok(Some((line, _))) => self.read_line(line),
+                Ok(None) => {
+                    self.lines = None;
+                    self.finish();
+                }
+                Err(error) => {
+                    self.stop();
+                    return Some(Err(WhatsAppChatParser::transcript_error(&self.transcript_name, error)));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::com::zoho::arattai::core::date_format::DateOrder;
+
+    fn read(raw: &str, order: DateOrder) -> Option<String> {
+        let format = DateFormat { order, ..DateFormat::default() };
+        let text = WhatsAppChatParser::normalise_timestamp(raw);
+        format.parse(&text).map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+    }
+
+    #[test]
+    fn participants_stay_bounded() {
+        let mut participants = Participants::default();
+        for i in 0..MAX_SENDER_CANDIDATES * 2 {
+            participants.learn_line(&format!("Dr. A: Clinic: note {}: done", i));
+        }
+        assert!(participants.headed_lines.len() <= MAX_SENDER_CANDIDATES);
+        participants.forget_single_lines();
+        assert_eq!(participants.split("Dr. A: Clinic: hello"), Some(("Dr. A: Clinic", "hello")));
+        assert!(participants.is_known("Dr. A: Clinic"));
+    }
+
+    #[test]
+    fn undated_runs_are_held_back_up_to_a_cap() {
+        let undated = MAX_UNDATED * 3;
+        let mut transcript = String::from("12/03/2024, 9:00 am - Alice: one\n");
+        for i in 0..undated {
+            transcript.push_str(&format!("31/02/2024, 9:01 am - {}: lost {}\n", ["Bob", "Alice"][i % 2], i));
+        }
+        transcript.push_str("12/03/2024, 9:10 am - Bob: found\n");
+        let mut writer = zip::ZipWriter::new(io::Cursor::new(Vec::new()));
+        writer.start_file("_chat.txt", zip::write::SimpleFileOptions::default()).unwrap();
+        io::Write::write_all(&mut writer, transcript.as_bytes()).unwrap();
+        let export = writer.finish().unwrap().into_inner();
+
+        let mut stream = WhatsAppChatParser::messages_from_bytes(&export, &ParseOptions::new()).unwrap();
+        let mut count = 0;
+        while let Some(message) = stream.next() {
+            message.unwrap();
+            count += 1;
+            assert!(stream.undated.len() < MAX_UNDATED);
+            assert!(stream.ready.len() < MAX_UNDATED);
+            // The run is let through before the transcript is read to its end.
+            if count == MAX_UNDATED + 1 {
+                assert!(stream.lines.is_some());
+            }
+        }
+        assert_eq!(count, undated + 2);
+    }
+
+    #[test]
+    fn limited_reader_fails_past_its_limit() {
+        let mut buf = Vec::new();
+        Limited::new(&b"abcd"[..], "chat.txt", 4).read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, b"abcd");
+
+        let error = Limited::new(&b"abcde"[..], "chat.txt", 4).read_to_end(&mut Vec::new()).unwrap_err();
+        match ParseError::from(error) {
+            ParseError::LimitExceeded(reason) => assert_eq!(reason, "chat.txt holds more than 4 bytes"),
+            other => panic!("expected a limit error, got {:?}", other),
+        }
+        let error = Limited::declared(&b"abcde"[..], "a.jpg", 4).read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn reads_a_location_behind_a_non_ascii_prefix() {
+        let turkish = Locale::by_code("tr").unwrap();
+        // The Kelvin sign lowercases to `k`, shortening the prefix by two bytes.
+        let body = "\u{212a}ONUM: https://maps.google.com/?q=41.0082,28.9784";
+        let location = WhatsAppChatParser::parse_location(body, turkish).unwrap();
+        assert_eq!(location.url, "https://maps.google.com/?q=41.0082,28.9784");
+        assert_eq!((location.latitude, location.longitude), (41.0082, 28.9784));
+        assert!(!location.live);
+    }
+
+    #[test]
+    fn reads_a_poll_behind_a_non_ascii_prefix() {
+        let polish = Locale::by_code("pl").unwrap();
+        // The Kelvin sign lowercases to `k`, shortening the prefix by two bytes.
+        let body = "AN\u{212a}IETA: Obiad?\nOPCJA: Żurek (2 głosy)\nOpcja: Pierogi (0 głosów)";
+        let poll = WhatsAppChatParser::parse_poll(body, polish).unwrap();
+        assert_eq!(poll.question, "Obiad?");
+        let options: Vec<_> = poll.options.iter().map(|o| (o.text.as_str(), o.votes)).collect();
+        assert_eq!(options, [("Żurek", 2), ("Pierogi", 0)]);
+    }
+
+    #[test]
+    fn reads_japanese_markers() {
+        let ymd = DateOrder::YearMonthDay;
+        assert_eq!(read("2024/03/12 午前9:01", ymd).as_deref(), Some("2024-03-12 09:01"));
+        assert_eq!(read("2024/03/12 午後9:01", ymd).as_deref(), Some("2024-03-12 21:01"));
+        assert_eq!(read("2024/03/12 午前0:30", ymd).as_deref(), Some("2024-03-12 00:30"));
+        assert_eq!(read("2024/03/12 午後0:05", ymd).as_deref(), Some("2024-03-12 12:05"));
+    }
+
+    #[test]
+    fn reads_spanish_markers() {
+        let dmy = DateOrder::DayMonthYear;
+        assert_eq!(read("12/3/24, 9:01\u{a0}a.\u{a0}m.", dmy).as_deref(), Some("2024-03-12 09:01"));
+        assert_eq!(read("12/3/24, 9:01\u{202f}p.\u{202f}m.", dmy).as_deref(), Some("2024-03-12 21:01"));
+    }
+
+    #[test]
+    fn reads_arabic_indic_digits_and_markers() {
+        let dmy = DateOrder::DayMonthYear;
+        assert_eq!(read("١٢\u{200f}/٣\u{200f}/٢٠٢٤، ٩:٠١ ص", dmy).as_deref(), Some("2024-03-12 09:01"));
+        assert_eq!(read("١٢\u{200f}/٣\u{200f}/٢٠٢٤، ٩:٠١ م", dmy).as_deref(), Some("2024-03-12 21:01"));
+    }
+
+    #[test]
+    fn reads_devanagari_digits_and_markers() {
+        let dmy = DateOrder::DayMonthYear;
+        assert_eq!(read("१२/३/२४, ९:०१ पूर्वाह्न", dmy).as_deref(), Some("2024-03-12 09:01"));
+        assert_eq!(read("१२/३/२४, ९:०१ अपराह्न", dmy).as_deref(), Some("2024-03-12 21:01"));
     }
 }
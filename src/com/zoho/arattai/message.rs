@@ -1,6 +1,12 @@
 pub mod audio_messages;
+pub mod call_messages;
+pub mod contact_messages;
 pub mod document_messages;
 pub mod image_messages;
+pub mod location_messages;
+pub mod omitted_media_messages;
+pub mod poll_messages;
 pub mod sticker_messages;
+pub mod system_messages;
 pub mod text_messages;
 pub mod video_messages;
\ No newline at end of file
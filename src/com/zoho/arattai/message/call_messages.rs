@@ -0,0 +1,43 @@
+use crate::com::zoho::arattai::core::messages::MessageBase;
+
+/// Represents a voice or video call entry parsed from a WhatsApp chat export.
+#[derive(Debug, Clone)]
+pub struct CallMessage {
+    pub base: MessageBase,
+    /// Whether this was a video call rather than a voice call.
+    pub video: bool,
+    /// Whether the call went unanswered.
+    pub missed: bool,
+    /// How long the call lasted, formatted as "m:ss", or empty if unknown.
+    pub duration: String,
+    /// Whether this was a group call.
+    pub group: bool,
+}
+
+impl CallMessage {
+    pub fn new(base: MessageBase, video: bool, missed: bool, duration: String, group: bool) -> Self {
+        Self {
+            base,
+            video,
+            missed,
+            duration,
+            group,
+        }
+    }
+
+    pub fn is_video(&self) -> bool {
+        self.video
+    }
+
+    pub fn is_missed(&self) -> bool {
+        self.missed
+    }
+
+    pub fn get_duration(&self) -> &str {
+        &self.duration
+    }
+
+    pub fn is_group(&self) -> bool {
+        self.group
+    }
+}
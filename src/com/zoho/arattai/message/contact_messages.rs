@@ -0,0 +1,36 @@
+use crate::com::zoho::arattai::core::messages::{ContactCard, MessageBase};
+
+/// Represents a shared contact card (`.vcf`) attachment parsed from a WhatsApp chat export.
+#[derive(Debug, Clone)]
+pub struct ContactMessage {
+    pub base: MessageBase,
+    /// The filename of the vCard as stored inside the export ZIP.
+    pub name: String,
+    /// The uncompressed file size of the vCard in bytes.
+    pub size: u64,
+    /// Every contact in the vCard, or empty if the file is missing from the export.
+    pub contacts: Vec<ContactCard>,
+}
+
+impl ContactMessage {
+    pub fn new(base: MessageBase, name: String, size: u64, contacts: Vec<ContactCard>) -> Self {
+        Self {
+            base,
+            name,
+            size,
+            contacts,
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn get_contacts(&self) -> &[ContactCard] {
+        &self.contacts
+    }
+}
@@ -10,15 +10,18 @@ pub struct DocumentMessage {
     pub extension: String,
     /// The uncompressed file size of the document in bytes.
     pub size: u64,
+    /// The text sent alongside the document, or empty if there was none.
+    pub caption: String,
 }
 
 impl DocumentMessage {
-    pub fn new(base: MessageBase, name: String, extension: String, size: u64) -> Self {
+    pub fn new(base: MessageBase, name: String, extension: String, size: u64, caption: String) -> Self {
         Self {
             base,
             name,
             extension,
             size,
+            caption,
         }
     }
 
@@ -33,4 +36,8 @@ impl DocumentMessage {
     pub fn get_size(&self) -> u64 {
         self.size
     }
+
+    pub fn get_caption(&self) -> &str {
+        &self.caption
+    }
 }
@@ -14,6 +14,8 @@ pub struct ImageMessage {
     pub size: u64,
     /// The lowercase file extension that identifies the image format.
     pub extension: String,
+    /// The text sent alongside the image, or empty if there was none.
+    pub caption: String,
 }
 
 impl ImageMessage {
@@ -24,6 +26,7 @@ impl ImageMessage {
         width: u32,
         size: u64,
         extension: String,
+        caption: String,
     ) -> Self {
         Self {
             base,
@@ -32,6 +35,7 @@ impl ImageMessage {
             width,
             size,
             extension,
+            caption,
         }
     }
 
@@ -54,4 +58,8 @@ impl ImageMessage {
     pub fn get_extension(&self) -> &str {
         &self.extension
     }
+
+    pub fn get_caption(&self) -> &str {
+        &self.caption
+    }
 }
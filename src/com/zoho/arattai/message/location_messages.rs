@@ -0,0 +1,65 @@
+use crate::com::zoho::arattai::core::messages::MessageBase;
+
+/// Represents a shared location or live location parsed from a WhatsApp chat export.
+#[derive(Debug, Clone)]
+pub struct LocationMessage {
+    pub base: MessageBase,
+    /// The latitude of the shared pin in decimal degrees.
+    pub latitude: f64,
+    /// The longitude of the shared pin in decimal degrees.
+    pub longitude: f64,
+    /// The place name shared with the pin, or empty for a dropped pin.
+    pub name: String,
+    /// The address lines shared below the place name.
+    pub address_lines: Vec<String>,
+    /// Whether the sender shared their live location rather than a fixed pin.
+    pub live: bool,
+    /// The maps link exactly as it appears in the WhatsApp transcript.
+    pub url: String,
+}
+
+impl LocationMessage {
+    pub fn new(
+        base: MessageBase,
+        latitude: f64,
+        longitude: f64,
+        name: String,
+        address_lines: Vec<String>,
+        live: bool,
+        url: String,
+    ) -> Self {
+        Self {
+            base,
+            latitude,
+            longitude,
+            name,
+            address_lines,
+            live,
+            url,
+        }
+    }
+
+    pub fn get_latitude(&self) -> f64 {
+        self.latitude
+    }
+
+    pub fn get_longitude(&self) -> f64 {
+        self.longitude
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_address_lines(&self) -> &[String] {
+        &self.address_lines
+    }
+
+    pub fn is_live(&self) -> bool {
+        self.live
+    }
+
+    pub fn get_url(&self) -> &str {
+        &self.url
+    }
+}
@@ -0,0 +1,14 @@
+use crate::com::zoho::arattai::core::messages::MessageBase;
+
+/// Represents an attachment left out of a WhatsApp export made without
+/// media, when the transcript does not say what kind of media it was.
+#[derive(Debug, Clone)]
+pub struct OmittedMediaMessage {
+    pub base: MessageBase,
+}
+
+impl OmittedMediaMessage {
+    pub fn new(base: MessageBase) -> Self {
+        Self { base }
+    }
+}
@@ -0,0 +1,36 @@
+use crate::com::zoho::arattai::core::messages::{MessageBase, PollOption};
+
+/// Represents a poll parsed from a WhatsApp chat export.
+#[derive(Debug, Clone)]
+pub struct PollMessage {
+    pub base: MessageBase,
+    /// The question the poll asks.
+    pub question: String,
+    /// The options in the order they were offered, with their vote counts.
+    pub options: Vec<PollOption>,
+    /// Whether voters could pick more than one option.
+    pub multi_select: bool,
+}
+
+impl PollMessage {
+    pub fn new(base: MessageBase, question: String, options: Vec<PollOption>, multi_select: bool) -> Self {
+        Self {
+            base,
+            question,
+            options,
+            multi_select,
+        }
+    }
+
+    pub fn get_question(&self) -> &str {
+        &self.question
+    }
+
+    pub fn get_options(&self) -> &[PollOption] {
+        &self.options
+    }
+
+    pub fn is_multi_select(&self) -> bool {
+        self.multi_select
+    }
+}
@@ -0,0 +1,58 @@
+use crate::com::zoho::arattai::core::messages::{MessageBase, SystemEventKind};
+
+/// Represents a sender-less event line parsed from a WhatsApp chat export,
+/// such as the encryption notice or a participant joining a group.
+#[derive(Debug, Clone)]
+pub struct SystemMessage {
+    pub base: MessageBase,
+    /// The event text exactly as it appears in the WhatsApp transcript.
+    pub text: String,
+    /// The recognised event, or `SystemEventKind::Other`.
+    pub kind: SystemEventKind,
+    /// The participants who caused the event.
+    pub actors: Vec<String>,
+    /// The participants whose membership or admin role the event changed.
+    pub subjects: Vec<String>,
+    /// What else the event acted on, or an empty string if it has no target.
+    pub target: String,
+}
+
+impl SystemMessage {
+    pub fn new(
+        base: MessageBase,
+        text: String,
+        kind: SystemEventKind,
+        actors: Vec<String>,
+        subjects: Vec<String>,
+        target: String,
+    ) -> Self {
+        Self {
+            base,
+            text,
+            kind,
+            actors,
+            subjects,
+            target,
+        }
+    }
+
+    pub fn get_text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn get_kind(&self) -> SystemEventKind {
+        self.kind
+    }
+
+    pub fn get_actors(&self) -> &[String] {
+        &self.actors
+    }
+
+    pub fn get_subjects(&self) -> &[String] {
+        &self.subjects
+    }
+
+    pub fn get_target(&self) -> &str {
+        &self.target
+    }
+}
@@ -16,9 +16,12 @@ pub struct VideoMessage {
     pub width: u32,
     /// The vertical resolution of the video in pixels, or 0 if unknown.
     pub height: u32,
+    /// The text sent alongside the video, or empty if there was none.
+    pub caption: String,
 }
 
 impl VideoMessage {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         base: MessageBase,
         name: String,
@@ -27,6 +30,7 @@ impl VideoMessage {
         extension: String,
         width: u32,
         height: u32,
+        caption: String,
     ) -> Self {
         Self {
             base,
@@ -36,6 +40,7 @@ impl VideoMessage {
             extension,
             width,
             height,
+            caption,
         }
     }
 
@@ -62,4 +67,8 @@ impl VideoMessage {
     pub fn get_height(&self) -> u32 {
         self.height
     }
+
+    pub fn get_caption(&self) -> &str {
+        &self.caption
+    }
 }
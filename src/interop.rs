@@ -1,6 +1,9 @@
 #[cfg(not(target_arch = "wasm32"))]
-use std::ffi::CStr;
-use crate::com::zoho::arattai::core::messages::WhatsAppExport as ProtoExport;
+use std::ffi::{CStr, CString};
+use crate::com::zoho::arattai::core::delimited_export::DelimitedExport;
+use crate::com::zoho::arattai::core::messages::ParseOptions as ProtoOptions;
+use crate::com::zoho::arattai::core::parse_error::ParseError;
+use crate::com::zoho::arattai::core::parse_options::ParseOptions;
 use crate::com::zoho::arattai::core::whats_app_parse::WhatsAppChatParser;
 use prost::Message;
 #[cfg(not(target_arch = "wasm32"))]
@@ -10,63 +13,275 @@ use wasm_bindgen::prelude::*;
 #[cfg(target_os = "android")]
 use jni::JNIEnv;
 #[cfg(target_os = "android")]
-use jni::objects::{JClass, JString};
+use jni::objects::{JClass, JString, JThrowable, JValue};
 #[cfg(target_os = "android")]
-use jni::sys::jbyteArray;
+use jni::sys::{jbyteArray, jint, jlong};
+#[cfg(target_os = "android")]
+use jni::objects::JByteArray;
+
+/// Reads a serialized `ParseOptions` message; an empty buffer gives the
+/// defaults.
+fn decode_options(bytes: &[u8]) -> Result<ParseOptions, ParseError> {
+    let proto = ProtoOptions::decode(bytes).map_err(|e| ParseError::InvalidInput(format!("malformed options: {}", e)))?;
+    ParseOptions::from_proto(&proto)
+}
 
+/// The result of a C-FFI call. On success `data` holds the serialized
+/// Protobuf and `error_code` is 0; on failure `data` is null, `error_code`
+/// is the `ParseError` code and `error_message` a NUL-terminated message.
 #[cfg(not(target_arch = "wasm32"))]
 #[repr(C)]
 pub struct ByteBuffer {
     pub data: *mut u8,
     pub len: size_t,
+    pub error_code: u32,
+    pub error_message: *mut c_char,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ByteBuffer {
+    fn from_result(result: Result<Vec<u8>, ParseError>) -> Self {
+        match result {
+            Ok(buf) => {
+                let len = buf.len();
+                let data = Box::into_raw(buf.into_boxed_slice()) as *mut u8;
+                ByteBuffer { data, len, error_code: 0, error_message: std::ptr::null_mut() }
+            }
+            Err(error) => {
+                let message = CString::new(error.to_string().replace('\0', " ")).unwrap_or_default();
+                ByteBuffer {
+                    data: std::ptr::null_mut(),
+                    len: 0,
+                    error_code: error.code(),
+                    error_message: message.into_raw(),
+                }
+            }
+        }
+    }
 }
 
 /// C-FFI Bridge for iOS and Desktop.
 /// Returns a serialized Protobuf buffer inside a C-compatible struct.
 #[cfg(not(target_arch = "wasm32"))]
 #[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // Null is checked; callers pass C strings.
 pub extern "C" fn parse_chat_ffi(path: *const c_char) -> ByteBuffer {
-    if path.is_null() { return ByteBuffer { data: std::ptr::null_mut(), len: 0 }; }
-    let c_str = unsafe { CStr::from_ptr(path) };
-    let path_str = match c_str.to_str() { Ok(s) => s, Err(_) => return ByteBuffer { data: std::ptr::null_mut(), len: 0 } };
-    match WhatsAppChatParser::parse(path_str) {
-        Ok(export) => {
-            let proto_export = ProtoExport { chat_name: export.get_chat_name().to_string(), messages: export.get_all_messages().to_vec() };
-            let mut buf = Vec::new();
-            if proto_export.encode(&mut buf).is_ok() {
-                let len = buf.len();
-                let data = Box::into_raw(buf.into_boxed_slice()) as *mut u8;
-                ByteBuffer { data, len }
-            } else { ByteBuffer { data: std::ptr::null_mut(), len: 0 } }
+    unsafe { parse_chat_named_ffi(path, std::ptr::null()) }
+}
+
+/// Same as `parse_chat_ffi`, but names the chat `chat_name` instead of
+/// inferring the name from the export. A null `chat_name` infers it.
+///
+/// # Safety
+/// `path` and `chat_name` must each be null or point to a NUL-terminated
+/// C string.
+#[cfg(not(target_arch = "wasm32"))]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn parse_chat_named_ffi(path: *const c_char, chat_name: *const c_char) -> ByteBuffer {
+    let read = |ptr: *const c_char, what: &str| -> Result<Option<&str>, ParseError> {
+        if ptr.is_null() { return Ok(None); }
+        let c_str = unsafe { CStr::from_ptr(ptr) };
+        c_str.to_str().map(Some).map_err(|_| ParseError::InvalidInput(format!("{} is not valid UTF-8", what)))
+    };
+    let result = read(path, "path").and_then(|path_str| {
+        let path_str = path_str.ok_or_else(|| ParseError::InvalidInput("path is null".to_string()))?;
+        let mut options = ParseOptions::new();
+        if let Some(chat_name) = read(chat_name, "chat name")? {
+            options = options.chat_name(chat_name);
+        }
+        let export = WhatsAppChatParser::parse_with(path_str, &options)?;
+        Ok(export.to_proto().encode_to_vec())
+    });
+    ByteBuffer::from_result(result)
+}
+
+/// Same as `parse_chat_ffi`, configured by a serialized `ParseOptions`
+/// message of `options_len` bytes. A null `options` uses the defaults.
+///
+/// # Safety
+/// `path` must be null or point to a NUL-terminated C string, and
+/// `options` must be null or point to `options_len` readable bytes.
+#[cfg(not(target_arch = "wasm32"))]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn parse_chat_with_options_ffi(
+    path: *const c_char,
+    options: *const u8,
+    options_len: size_t,
+) -> ByteBuffer {
+    let result = unsafe { read_ffi_request(path, options, options_len) }.and_then(|(path_str, options)| {
+        let export = WhatsAppChatParser::parse_with(path_str, &options)?;
+        Ok(export.to_proto().encode_to_vec())
+    });
+    ByteBuffer::from_result(result)
+}
+
+/// Reads the path and serialized `ParseOptions` passed to an FFI call.
+///
+/// # Safety
+/// As for `parse_chat_with_options_ffi`.
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn read_ffi_request<'p>(
+    path: *const c_char,
+    options: *const u8,
+    options_len: size_t,
+) -> Result<(&'p str, ParseOptions), ParseError> {
+    if path.is_null() { return Err(ParseError::InvalidInput("path is null".to_string())); }
+    let path_str = unsafe { CStr::from_ptr(path) }
+        .to_str()
+        .map_err(|_| ParseError::InvalidInput("path is not valid UTF-8".to_string()))?;
+    let bytes = if options.is_null() { &[][..] } else { unsafe { std::slice::from_raw_parts(options, options_len) } };
+    Ok((path_str, decode_options(bytes)?))
+}
+
+/// Opens an export for streaming, configured as for
+/// `parse_chat_with_options_ffi`. On success `*stream` receives a handle
+/// to pass to `next_chat_chunk_ffi` and the returned buffer is empty; on
+/// failure `*stream` is null and the buffer carries the error. The handle
+/// must be released with `close_chat_stream_ffi`.
+///
+/// # Safety
+/// As for `parse_chat_with_options_ffi`, and `stream` must point to
+/// writable memory for one pointer.
+#[cfg(not(target_arch = "wasm32"))]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn open_chat_stream_ffi(
+    path: *const c_char,
+    options: *const u8,
+    options_len: size_t,
+    stream: *mut *mut ChatStream,
+) -> ByteBuffer {
+    let result = unsafe { read_ffi_request(path, options, options_len) }
+        .and_then(|(path_str, options)| WhatsAppChatParser::messages(path_str, &options));
+    match result {
+        Ok(messages) => {
+            let handle = Box::new(ChatStream { export: DelimitedExport::new(messages) });
+            unsafe { *stream = Box::into_raw(handle) };
+            ByteBuffer::from_result(Ok(Vec::new()))
+        }
+        Err(error) => {
+            unsafe { *stream = std::ptr::null_mut() };
+            ByteBuffer::from_result(Err(error))
         }
-        Err(_) => ByteBuffer { data: std::ptr::null_mut(), len: 0 },
     }
 }
 
-/// Frees the memory allocated by `parse_chat_ffi`.
+/// Returns the next records of a streamed export, at least `chunk_size`
+/// bytes of whole records unless the export ends first. An empty buffer
+/// without an error marks the end. Free each buffer with
+/// `free_byte_buffer`.
+///
+/// # Safety
+/// `stream` must be a handle from `open_chat_stream_ffi` that has not
+/// been closed.
+#[cfg(not(target_arch = "wasm32"))]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn next_chat_chunk_ffi(stream: *mut ChatStream, chunk_size: size_t) -> ByteBuffer {
+    let Some(stream) = (unsafe { stream.as_mut() }) else {
+        return ByteBuffer::from_result(Err(ParseError::InvalidInput("stream is null".to_string())));
+    };
+    ByteBuffer::from_result(stream.export.next_chunk(chunk_size))
+}
+
+/// Releases a handle from `open_chat_stream_ffi`, whether or not the
+/// export was read to the end.
+///
+/// # Safety
+/// `stream` must be null or a handle from `open_chat_stream_ffi` that has
+/// not been closed.
+#[cfg(not(target_arch = "wasm32"))]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn close_chat_stream_ffi(stream: *mut ChatStream) {
+    if !stream.is_null() { unsafe { let _ = Box::from_raw(stream); } }
+}
+
+/// Frees the memory allocated by `parse_chat_ffi`, including the error
+/// message of a failed call.
 #[cfg(not(target_arch = "wasm32"))]
 #[unsafe(no_mangle)]
 pub extern "C" fn free_byte_buffer(buffer: ByteBuffer) {
-    if !buffer.data.is_null() { unsafe { let _ = Box::from_raw(std::slice::from_raw_parts_mut(buffer.data, buffer.len)); } }
+    if !buffer.data.is_null() { unsafe { let _ = Box::from_raw(std::ptr::slice_from_raw_parts_mut(buffer.data, buffer.len)); } }
+    if !buffer.error_message.is_null() { unsafe { let _ = CString::from_raw(buffer.error_message); } }
+}
+
+/// A failed WASM call as JavaScript sees it: the thrown value carries the
+/// `ParseError` code and message.
+#[wasm_bindgen]
+pub struct ParseFailure {
+    code: u32,
+    message: String,
+}
+
+#[wasm_bindgen]
+impl ParseFailure {
+    #[wasm_bindgen(getter)]
+    pub fn code(&self) -> u32 {
+        self.code
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> String {
+        self.message.clone()
+    }
+}
+
+impl From<ParseError> for ParseFailure {
+    fn from(error: ParseError) -> Self {
+        ParseFailure { code: error.code(), message: error.to_string() }
+    }
+}
+
+/// An export being streamed to a bridge as length-delimited records; see
+/// `DelimitedExport`. JavaScript pulls chunks with `next_chunk`, C holds
+/// it as an opaque handle and Kotlin as a `Long`.
+#[wasm_bindgen]
+pub struct ChatStream {
+    export: DelimitedExport<'static>,
+}
+
+#[wasm_bindgen]
+impl ChatStream {
+    /// Returns the next records, at least `chunk_size` bytes of whole
+    /// records unless the export ends first, or `undefined` at the end.
+    pub fn next_chunk(&mut self, chunk_size: usize) -> Result<Option<Vec<u8>>, ParseFailure> {
+        let chunk = self.export.next_chunk(chunk_size)?;
+        Ok(Some(chunk).filter(|chunk| !chunk.is_empty()))
+    }
 }
 
 /// WASM Bridge for Web.
-/// Takes a byte slice and returns a Vector of bytes (Protobuf).
+/// Takes a byte slice and returns a Vector of bytes (Protobuf), or throws
+/// a `ParseFailure`.
+/// `chat_name` may be left undefined to infer the name from the export.
 #[wasm_bindgen]
-pub fn parse_chat_wasm(zip_bytes: &[u8]) -> Vec<u8> {
-    match WhatsAppChatParser::parse_bytes(zip_bytes) {
-        Ok(export) => {
-            let proto_export = ProtoExport { chat_name: export.get_chat_name().to_string(), messages: export.get_all_messages().to_vec() };
-            let mut buf = Vec::new();
-            if proto_export.encode(&mut buf).is_ok() { buf } else { Vec::new() }
-        }
-        Err(_) => Vec::new(),
+pub fn parse_chat_wasm(zip_bytes: &[u8], chat_name: Option<String>) -> Result<Vec<u8>, ParseFailure> {
+    let mut options = ParseOptions::new();
+    if let Some(chat_name) = chat_name {
+        options = options.chat_name(chat_name);
     }
+    let export = WhatsAppChatParser::parse_bytes_with(zip_bytes, &options)?;
+    Ok(export.to_proto().encode_to_vec())
 }
 
-/// JNI bridge for Android. 
+/// Same as `parse_chat_wasm`, configured by a serialized `ParseOptions`
+/// message.
+#[wasm_bindgen]
+pub fn parse_chat_with_options_wasm(zip_bytes: &[u8], options: &[u8]) -> Result<Vec<u8>, ParseFailure> {
+    let export = WhatsAppChatParser::parse_bytes_with(zip_bytes, &decode_options(options)?)?;
+    Ok(export.to_proto().encode_to_vec())
+}
+
+/// Opens a ZIP export for streaming, configured by a serialized
+/// `ParseOptions` message. The stream keeps its own copy of the bytes.
+#[wasm_bindgen]
+pub fn open_chat_stream_wasm(zip_bytes: Vec<u8>, options: &[u8]) -> Result<ChatStream, ParseFailure> {
+    let messages = WhatsAppChatParser::messages_from_vec(zip_bytes, &decode_options(options)?)?;
+    Ok(ChatStream { export: DelimitedExport::new(messages) })
+}
+
+/// JNI bridge for Android.
 /// The function name MUST match the package and class name in Kotlin.
 /// Kotlin: package com.example.imported_rust -> class WhatsAppAndroidConnector -> external fun parseChatNative
+/// Failures are thrown as `WhatsAppParseException(code, message)`.
 #[cfg(target_os = "android")]
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn Java_com_example_imported_1rust_WhatsAppAndroidConnector_parseChatNative(
@@ -74,30 +289,180 @@ pub unsafe extern "C" fn Java_com_example_imported_1rust_WhatsAppAndroidConnecto
     _class: JClass,
     path: JString,
 ) -> jbyteArray {
+    parse_chat_jni(&mut env, &path, &ParseOptions::new())
+}
+
+/// JNI bridge for Android that names the chat `chatName`, or infers the
+/// name from the export when it is null.
+/// Kotlin: external fun parseChatNamedNative(path: String, chatName: String?): ByteArray?
+#[cfg(target_os = "android")]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn Java_com_example_imported_1rust_WhatsAppAndroidConnector_parseChatNamedNative(
+    mut env: JNIEnv,
+    _class: JClass,
+    path: JString,
+    chat_name: JString,
+) -> jbyteArray {
+    let chat_name: Option<String> = if chat_name.is_null() {
+        None
+    } else {
+        match env.get_string(&chat_name) {
+            Ok(s) => Some(s.into()),
+            Err(_) => {
+                throw_parse_error(&mut env, &ParseError::InvalidInput("chat name is not a valid string".to_string()));
+                return std::ptr::null_mut();
+            }
+        }
+    };
+    let mut options = ParseOptions::new();
+    if let Some(chat_name) = chat_name {
+        options = options.chat_name(chat_name);
+    }
+    parse_chat_jni(&mut env, &path, &options)
+}
+
+/// JNI bridge for Android configured by a serialized `ParseOptions`
+/// message, or the defaults when `options` is null.
+/// Kotlin: external fun parseChatWithOptionsNative(path: String, options: ByteArray?): ByteArray?
+#[cfg(target_os = "android")]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn Java_com_example_imported_1rust_WhatsAppAndroidConnector_parseChatWithOptionsNative(
+    mut env: JNIEnv,
+    _class: JClass,
+    path: JString,
+    options: JByteArray,
+) -> jbyteArray {
+    match read_options_jni(&mut env, &options) {
+        Ok(options) => parse_chat_jni(&mut env, &path, &options),
+        Err(error) => {
+            throw_parse_error(&mut env, &error);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// JNI bridge for Android that opens an export for streaming, configured
+/// as for `parseChatWithOptionsNative`. Returns a handle for
+/// `nextChatChunkNative`, which must be released with
+/// `closeChatStreamNative`.
+/// Kotlin: external fun openChatStreamNative(path: String, options: ByteArray?): Long
+#[cfg(target_os = "android")]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn Java_com_example_imported_1rust_WhatsAppAndroidConnector_openChatStreamNative(
+    mut env: JNIEnv,
+    _class: JClass,
+    path: JString,
+    options: JByteArray,
+) -> jlong {
+    let result = read_options_jni(&mut env, &options).and_then(|options| {
+        let path_str: String = env
+            .get_string(&path)
+            .map_err(|_| ParseError::InvalidInput("path is not a valid string".to_string()))?
+            .into();
+        WhatsAppChatParser::messages(path_str, &options)
+    });
+    match result {
+        Ok(messages) => Box::into_raw(Box::new(ChatStream { export: DelimitedExport::new(messages) })) as jlong,
+        Err(error) => {
+            throw_parse_error(&mut env, &error);
+            0
+        }
+    }
+}
+
+/// JNI bridge for Android that returns the next records of a streamed
+/// export, at least `chunkSize` bytes of whole records unless the export
+/// ends first, or null at the end.
+/// Kotlin: external fun nextChatChunkNative(handle: Long, chunkSize: Int): ByteArray?
+#[cfg(target_os = "android")]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn Java_com_example_imported_1rust_WhatsAppAndroidConnector_nextChatChunkNative(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    chunk_size: jint,
+) -> jbyteArray {
+    let Some(stream) = (unsafe { (handle as *mut ChatStream).as_mut() }) else {
+        throw_parse_error(&mut env, &ParseError::InvalidInput("stream is closed".to_string()));
+        return std::ptr::null_mut();
+    };
+    match stream.export.next_chunk(chunk_size.max(0) as usize) {
+        Ok(chunk) if chunk.is_empty() => std::ptr::null_mut(),
+        Ok(chunk) => match env.byte_array_from_slice(&chunk) {
+            Ok(arr) => arr.as_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(error) => {
+            throw_parse_error(&mut env, &error);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// JNI bridge for Android that releases a handle from
+/// `openChatStreamNative`.
+/// Kotlin: external fun closeChatStreamNative(handle: Long)
+#[cfg(target_os = "android")]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn Java_com_example_imported_1rust_WhatsAppAndroidConnector_closeChatStreamNative(
+    _env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+) {
+    if handle != 0 { unsafe { let _ = Box::from_raw(handle as *mut ChatStream); } }
+}
+
+/// Reads a serialized `ParseOptions` passed from Kotlin; null gives the
+/// defaults.
+#[cfg(target_os = "android")]
+fn read_options_jni(env: &mut JNIEnv, options: &JByteArray) -> Result<ParseOptions, ParseError> {
+    if options.is_null() {
+        return Ok(ParseOptions::new());
+    }
+    let bytes = env
+        .convert_byte_array(options)
+        .map_err(|_| ParseError::InvalidInput("options are not a readable byte array".to_string()))?;
+    decode_options(&bytes)
+}
+
+#[cfg(target_os = "android")]
+fn parse_chat_jni(env: &mut JNIEnv, path: &JString, options: &ParseOptions) -> jbyteArray {
     // 1. Get the path string from JNI
-    let path_str: String = match env.get_string(&path) {
+    let path_str: String = match env.get_string(path) {
         Ok(s) => s.into(),
-        Err(_) => return std::ptr::null_mut(),
+        Err(_) => {
+            throw_parse_error(env, &ParseError::InvalidInput("path is not a valid string".to_string()));
+            return std::ptr::null_mut();
+        }
     };
 
     // 2. Call the parser (same logic as FFI)
-    match WhatsAppChatParser::parse(&path_str) {
+    match WhatsAppChatParser::parse_with(&path_str, options) {
         Ok(export) => {
-            let proto_export = ProtoExport { 
-                chat_name: export.get_chat_name().to_string(), 
-                messages: export.get_all_messages().to_vec() 
-            };
-            let mut buf = Vec::new();
-            if proto_export.encode(&mut buf).is_ok() {
-                // 3. Convert Rust Vec<u8> to JNI jbyteArray raw pointer
-                match env.byte_array_from_slice(&buf) {
-                    Ok(arr) => arr.as_raw(),
-                    Err(_) => std::ptr::null_mut(),
-                }
-            } else {
-                std::ptr::null_mut()
+            let buf = export.to_proto().encode_to_vec();
+            // 3. Convert Rust Vec<u8> to JNI jbyteArray raw pointer
+            match env.byte_array_from_slice(&buf) {
+                Ok(arr) => arr.as_raw(),
+                Err(_) => std::ptr::null_mut(),
             }
         }
-        Err(_) => std::ptr::null_mut(),
+        Err(error) => {
+            throw_parse_error(env, &error);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Throws `com.example.imported_rust.WhatsAppParseException` carrying the
+/// error's code and message.
+#[cfg(target_os = "android")]
+fn throw_parse_error(env: &mut JNIEnv, error: &ParseError) {
+    let Ok(message) = env.new_string(error.to_string()) else { return };
+    if let Ok(exception) = env.new_object(
+        "com/example/imported_rust/WhatsAppParseException",
+        "(ILjava/lang/String;)V",
+        &[JValue::Int(error.code() as i32), JValue::Object(&message)],
+    ) {
+        let _ = env.throw(JThrowable::from(exception));
     }
-}
\ No newline at end of file
+}
@@ -18,9 +18,9 @@ pub mod com;
 pub mod interop;
 pub mod client;
 
-#[cfg(not(target_arch = "wasm32"))]
-use crate::com::zoho::arattai::core::messages::{WhatsAppExport as ProtoExport};
-use crate::com::zoho::arattai::core::messages::{whatsapp_message, Type, WhatsAppMessage};
+use crate::com::zoho::arattai::core::messages::{
+    whatsapp_message, SystemEventKind, TimestampStatus, Type, WhatsAppMessage,
+};
 use crate::com::zoho::arattai::core::whats_app_export::WhatsAppExport;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::com::zoho::arattai::core::whats_app_parse::WhatsAppChatParser;
@@ -28,9 +28,10 @@ use std::io;
 #[cfg(not(target_arch = "wasm32"))]
 use std::io::Write;
 
-fn format_proto_timestamp(ts: &Option<prost_types::Timestamp>) -> String {
+/// Formats a timestamp as the sender's wall-clock time by applying their UTC offset.
+fn format_proto_timestamp(ts: &Option<prost_types::Timestamp>, utc_offset_seconds: i32) -> String {
     if let Some(t) = ts {
-        let naive = chrono::DateTime::from_timestamp(t.seconds, t.nanos as u32)
+        let naive = chrono::DateTime::from_timestamp(t.seconds + utc_offset_seconds as i64, t.nanos as u32)
             .map(|dt| dt.with_timezone(&chrono::Utc))
             .unwrap_or_else(|| chrono::DateTime::<chrono::Utc>::from_naive_utc_and_offset(
                 chrono::NaiveDateTime::default(),
@@ -57,15 +58,15 @@ pub fn print_message(msg: &WhatsAppMessage, chat_name: &str) {
         whatsapp_message::Content::Image(m) => (
             m.base.as_ref().unwrap(),
             format!(
-                "Image Name: {}\nImage Height: {}\nImage Width: {}\nImage Size: {} bytes\nImage Extension: {}",
-                m.name, m.height, m.width, m.size, m.extension
+                "Image Name: {}\nImage Height: {}\nImage Width: {}\nImage Size: {} bytes\nImage Extension: {}\nImage Caption: {}",
+                m.name, m.height, m.width, m.size, m.extension, m.caption
             ),
         ),
         whatsapp_message::Content::Video(m) => (
             m.base.as_ref().unwrap(),
             format!(
-                "Video Name: {}\nVideo Size: {} bytes\nVideo Duration: {}\nVideoExtension: {}\nVideo Width: {}\nVideo Height: {}",
-                m.name, m.size, m.duration, m.extension, m.width, m.height
+                "Video Name: {}\nVideo Size: {} bytes\nVideo Duration: {}\nVideoExtension: {}\nVideo Width: {}\nVideo Height: {}\nVideo Caption: {}",
+                m.name, m.size, m.duration, m.extension, m.width, m.height, m.caption
             ),
         ),
         whatsapp_message::Content::Audio(m) => (
@@ -78,8 +79,8 @@ pub fn print_message(msg: &WhatsAppMessage, chat_name: &str) {
         whatsapp_message::Content::Document(m) => (
             m.base.as_ref().unwrap(),
             format!(
-                "Document Name: {}\nDocument Extension: {}\nDocument Size: {} bytes",
-                m.name, m.extension, m.size
+                "Document Name: {}\nDocument Extension: {}\nDocument Size: {} bytes\nDocument Caption: {}",
+                m.name, m.extension, m.size, m.caption
             ),
         ),
         whatsapp_message::Content::Sticker(m) => (
@@ -89,13 +90,74 @@ pub fn print_message(msg: &WhatsAppMessage, chat_name: &str) {
                 m.name, m.extension, m.size
             ),
         ),
+        whatsapp_message::Content::Contact(m) => (
+            m.base.as_ref().unwrap(),
+            format!(
+                "Contact File: {}\nContact Size: {} bytes\nContacts: {}",
+                m.name,
+                m.size,
+                m.contacts
+                    .iter()
+                    .map(|c| {
+                        let phones: Vec<String> = c.phones.iter().map(|p| format!("{} [{}]", p.number, p.types.join(","))).collect();
+                        format!("{} ({})", c.formatted_name, phones.join("; "))
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            ),
+        ),
+        whatsapp_message::Content::Poll(m) => (
+            m.base.as_ref().unwrap(),
+            format!(
+                "Poll Question: {}\nPoll Options: {}\nPoll Multi Select: {}",
+                m.question,
+                m.options.iter().map(|o| format!("{} ({})", o.text, o.votes)).collect::<Vec<_>>().join(", "),
+                m.multi_select
+            ),
+        ),
+        whatsapp_message::Content::Call(m) => (
+            m.base.as_ref().unwrap(),
+            format!(
+                "Call Video: {}\nCall Missed: {}\nCall Duration: {}\nCall Group: {}",
+                m.video, m.missed, m.duration, m.group
+            ),
+        ),
+        whatsapp_message::Content::OmittedMedia(m) => (m.base.as_ref().unwrap(), "Media: omitted".to_string()),
+        whatsapp_message::Content::Location(m) => (
+            m.base.as_ref().unwrap(),
+            format!(
+                "Location: {}, {}\nLocation Name: {}\nLocation Address: {}\nLive Location: {}\nLocation URL: {}",
+                m.latitude, m.longitude, m.name, m.address_lines.join(", "), m.live, m.url
+            ),
+        ),
+        whatsapp_message::Content::System(m) => (
+            m.base.as_ref().unwrap(),
+            format!(
+                "System Event: {}\nSystem Actors: {}\nSystem Subjects: {}\nSystem Target: {}\nSystem Text: {}",
+                SystemEventKind::try_from(m.kind).map(|k| format!("{:?}", k)).unwrap_or_else(|_| "Other".to_string()),
+                m.actors.join(", "), m.subjects.join(", "), m.target, m.text
+            ),
+        ),
     };
 
     println!("Chat Name: {}", chat_name);
     println!("Sender: {}", base.sender);
-    println!("Timestamp: {}", format_proto_timestamp(&base.timestamp));
+    println!("Timestamp: {}", format_proto_timestamp(&base.timestamp, base.utc_offset_seconds));
+    if base.timestamp_status != TimestampStatus::Exact as i32 {
+        let status = TimestampStatus::try_from(base.timestamp_status).map(|s| format!("{:?}", s)).unwrap_or_else(|_| "Unknown".to_string());
+        println!("Timestamp Status: {} (raw \"{}\")", status, base.raw_timestamp);
+    }
     let type_name = Type::try_from(base.r#type).map(|t| format!("{:?}", t)).unwrap_or_else(|_| "Unknown".to_string());
     println!("Type: {}", type_name);
+    if base.deleted {
+        println!("Deleted: true");
+    }
+    if base.edited {
+        println!("Edited: true");
+    }
+    if base.media_omitted {
+        println!("Media Omitted: true");
+    }
     println!("{}", details);
 }
 
@@ -147,14 +209,20 @@ pub fn run_app() -> Result<(), Box<dyn std::error::Error>> {
             println!("\n========== SUMMARY ==========");
             println!("Chat Name: {}", export.get_chat_name());
             println!("Total messages parsed: {}", export.get_all_messages().len());
+            println!("Date Format: {}", export.get_date_format());
+            println!("Time Zone: {}", export.get_time_zone());
+            println!("Locale: {}", export.get_locale());
+            println!("Group Events: {}", export.get_group_events().len());
+            println!("Diagnostics: {}", export.get_diagnostics().len());
             println!("=============================\n");
 
             print_all_messages(&export);
 
-            let proto_export = ProtoExport {
-                chat_name: export.get_chat_name().to_string(),
-                messages: export.get_all_messages().to_vec(),
-            };
+            for diagnostic in export.get_diagnostics() {
+                println!("[Line {}] {}", diagnostic.line, diagnostic.message);
+            }
+
+            let proto_export = export.to_proto();
 
             use prost::Message;
             let mut buf = Vec::new();
@@ -166,7 +234,7 @@ pub fn run_app() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
         Err(e) => {
-            eprintln!("Error: {}", e);
+            eprintln!("Error {}: {}", e.code(), e);
         }
     }
 
@@ -0,0 +1,69 @@
+mod common;
+
+use rust_core::com::zoho::arattai::core::messages::whatsapp_message::Content;
+use rust_core::com::zoho::arattai::core::messages::{MessageBase, WhatsAppExport};
+use rust_core::com::zoho::arattai::core::parse_options::ParseOptions;
+use rust_core::com::zoho::arattai::core::whats_app_parse::WhatsAppChatParser;
+
+/// Senders and text wrapped in the bidi controls iOS and right-to-left
+/// exports add, with a decomposed `é` in Renée's name.
+const TRANSCRIPT: &str = "\
+12/03/2024, 09:01 - \u{202a}+1 555 0100\u{202c}: Call me \u{200e}\u{2068}+1 555 0101\u{2069}
+12/03/2024, 09:02 - Rene\u{301}e: plain
+12/03/2024, 09:03 - \u{202a}+1 555 0100\u{202c}: again
+12/03/2024, 09:04 - Rene\u{301}e: \u{200f}שלום
+";
+
+fn parse(options: &ParseOptions) -> WhatsAppExport {
+    WhatsAppChatParser::parse_bytes_with(&common::transcript_export(TRANSCRIPT), options).unwrap().to_proto()
+}
+
+fn texts(export: &WhatsAppExport) -> Vec<(&MessageBase, &str)> {
+    export
+        .messages
+        .iter()
+        .map(|m| match m.content.as_ref() {
+            Some(Content::Text(t)) => (t.base.as_ref().unwrap(), t.text.as_str()),
+            other => panic!("expected a text message, got {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn strips_bidi_controls_and_keeps_the_raw_text() {
+    let export = parse(&ParseOptions::new());
+    let messages = texts(&export);
+    assert_eq!(messages.len(), 4);
+
+    let (base, text) = messages[0];
+    assert_eq!(base.sender, "+1 555 0100");
+    assert_eq!(base.raw_sender, "\u{202a}+1 555 0100\u{202c}");
+    assert_eq!(text, "Call me +1 555 0101");
+    assert_eq!(base.raw_text, "Call me \u{200e}\u{2068}+1 555 0101\u{2069}");
+
+    let (base, text) = messages[1];
+    assert_eq!(base.sender, "Renée");
+    assert_eq!(base.raw_sender, "Rene\u{301}e");
+    // Text that cleaning leaves alone keeps no raw copy.
+    assert_eq!((text, base.raw_text.as_str()), ("plain", ""));
+
+    let (base, text) = messages[3];
+    assert_eq!(base.sender, "Renée");
+    assert_eq!(text, "שלום");
+    assert_eq!(base.raw_text, "\u{200f}שלום");
+
+    let mut roster: Vec<_> = export.roster.iter().map(|period| period.participant.as_str()).collect();
+    roster.sort();
+    assert_eq!(roster, ["+1 555 0100", "Renée"]);
+}
+
+#[test]
+fn leaves_text_alone_when_cleaning_is_off() {
+    let export = parse(&ParseOptions::new().clean_text(false));
+    let messages = texts(&export);
+    let (base, text) = messages[0];
+    assert_eq!(base.sender, "\u{202a}+1 555 0100\u{202c}");
+    assert_eq!(text, "Call me \u{200e}\u{2068}+1 555 0101\u{2069}");
+    assert_eq!((base.raw_sender.as_str(), base.raw_text.as_str()), ("", ""));
+    assert_eq!(messages[1].0.sender, "Rene\u{301}e");
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use rust_core::com::zoho::arattai::core::messages::whatsapp_message::Content;
+use rust_core::com::zoho::arattai::core::messages::{CallMessage, Type};
+use rust_core::com::zoho::arattai::core::whats_app_parse::WhatsAppChatParser;
+
+/// iOS logs calls as messages from the caller, with the call's length or
+/// why it went unanswered after a comma.
+const TRANSCRIPT: &str = "\
+[12/03/24, 09:01:00] Alice: \u{200e}Voice call, \u{200e}12 min 5 sec
+[12/03/24, 09:30:00] Bob: \u{200e}Missed video call, \u{200e}Tap to call back
+[12/03/24, 10:00:00] Alice: \u{200e}Group video call, \u{200e}1 hr 2 min
+[12/03/24, 10:30:00] Bob: \u{200e}Video call, \u{200e}No answer
+[12/03/24, 11:00:00] Alice: Voice call later?
+[12/03/24, 11:01:00] Bob: Sure
+";
+
+fn call(content: Option<&Content>) -> (bool, bool, bool, &str) {
+    match content {
+        Some(Content::Call(CallMessage { video, missed, group, duration, base })) => {
+            assert_eq!(base.as_ref().unwrap().r#type, Type::Call as i32);
+            (*video, *missed, *group, duration.as_str())
+        }
+        other => panic!("expected a call, got {:?}", other),
+    }
+}
+
+#[test]
+fn reads_call_logs() {
+    let export = common::zip_export(&[("_chat.txt", TRANSCRIPT.as_bytes())]);
+    let export = WhatsAppChatParser::parse_bytes(&export).unwrap().to_proto();
+    assert_eq!(export.messages.len(), 6);
+
+    // Video, missed, group, duration.
+    assert_eq!(call(export.messages[0].content.as_ref()), (false, false, false, "12:05"));
+    assert_eq!(call(export.messages[1].content.as_ref()), (true, true, false, ""));
+    assert_eq!(call(export.messages[2].content.as_ref()), (true, false, true, "62:00"));
+    assert_eq!(call(export.messages[3].content.as_ref()), (true, true, false, ""));
+    assert!(matches!(export.messages[4].content, Some(Content::Text(_))));
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use rust_core::com::zoho::arattai::core::messages::whatsapp_message::Content;
+use rust_core::com::zoho::arattai::core::whats_app_parse::WhatsAppChatParser;
+
+const TRANSCRIPT: &str = "\
+12/03/2024, 09:01 - Alice: IMG-20240312-WA0001.jpg (file attached)
+Sunrise at the lake
+12/03/2024, 09:02 - Bob: VID-20240312-WA0002.mp4 (file attached)
+Two lines
+of caption
+12/03/2024, 09:03 - Alice: Plan.pdf (file attached)
+Agenda: draft
+12/03/2024, 09:04 - Bob: IMG-20240312-WA0003.jpg (file attached)
+";
+
+#[test]
+fn keeps_the_caption_under_an_attachment() {
+    let export = common::zip_export(&[
+        (common::TRANSCRIPT, TRANSCRIPT.as_bytes()),
+        ("IMG-20240312-WA0001.jpg", b"jpeg"),
+        ("VID-20240312-WA0002.mp4", b"mp4"),
+        ("Plan.pdf", b"%PDF"),
+        ("IMG-20240312-WA0003.jpg", b"jpeg"),
+    ]);
+    let export = WhatsAppChatParser::parse_bytes(&export).unwrap().to_proto();
+    assert_eq!(export.messages.len(), 4);
+
+    let captions: Vec<_> = export
+        .messages
+        .iter()
+        .map(|m| match m.content.as_ref() {
+            Some(Content::Image(image)) => (image.name.as_str(), image.caption.as_str()),
+            Some(Content::Video(video)) => (video.name.as_str(), video.caption.as_str()),
+            Some(Content::Document(document)) => (document.name.as_str(), document.caption.as_str()),
+            other => panic!("expected an attachment, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(
+        captions,
+        [
+            ("IMG-20240312-WA0001.jpg", "Sunrise at the lake"),
+            ("VID-20240312-WA0002.mp4", "Two lines\nof caption"),
+            ("Plan.pdf", "Agenda: draft"),
+            ("IMG-20240312-WA0003.jpg", ""),
+        ]
+    );
+}
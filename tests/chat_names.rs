@@ -0,0 +1,62 @@
+mod common;
+
+use rust_core::com::zoho::arattai::core::parse_options::ParseOptions;
+use rust_core::com::zoho::arattai::core::whats_app_parse::WhatsAppChatParser;
+
+const CHAT: &str = "\
+12/03/2024, 09:01 - Alice: hi
+12/03/2024, 09:02 - Bob: hello
+";
+
+const GROUP: &str = "\
+12/03/2024, 09:00 - Alice created group \"Hikers\"
+12/03/2024, 09:01 - Alice: hi
+12/03/2024, 09:02 - Bob changed the subject from \"Hikers\" to \"Hikers 2024\"
+12/03/2024, 09:03 - Bob: hello
+";
+
+fn name_of(files: &[(&str, &str)]) -> String {
+    let files: Vec<_> = files.iter().map(|(name, text)| (*name, text.as_bytes())).collect();
+    let export = WhatsAppChatParser::parse_bytes(&common::zip_export(&files)).unwrap();
+    export.get_chat_name().to_string()
+}
+
+fn name_of_file(archive: &str, files: &[(&str, &str)]) -> String {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(archive);
+    let files: Vec<_> = files.iter().map(|(name, text)| (*name, text.as_bytes())).collect();
+    std::fs::write(&path, common::zip_export(&files)).unwrap();
+    WhatsAppChatParser::parse(&path).unwrap().get_chat_name().to_string()
+}
+
+#[test]
+fn names_the_chat_after_its_transcript() {
+    assert_eq!(name_of(&[("WhatsApp Chat with Priya.txt", CHAT)]), "Priya");
+    assert_eq!(name_of(&[("WhatsApp Chat with Priya (1).txt", CHAT)]), "Priya");
+    assert_eq!(name_of(&[("WhatsApp Chat - Book Club.txt", CHAT)]), "Book Club");
+    assert_eq!(name_of(&[("WhatsApp-Chat mit Jonas.txt", CHAT)]), "Jonas");
+    // The transcript's name wins over the group's subject.
+    assert_eq!(name_of(&[("WhatsApp Chat with Hikers.txt", GROUP)]), "Hikers");
+}
+
+#[test]
+fn names_the_chat_after_its_archive() {
+    assert_eq!(name_of_file("WhatsApp Chat - Book Club (2).zip", &[("_chat.txt", CHAT)]), "Book Club");
+    // A bare archive name comes after the group's subject.
+    assert_eq!(name_of_file("family.zip", &[("_chat.txt", GROUP)]), "Hikers 2024");
+    assert_eq!(name_of_file("family.zip", &[("_chat.txt", CHAT)]), "family");
+}
+
+#[test]
+fn names_the_chat_after_its_latest_subject() {
+    assert_eq!(name_of(&[("_chat.txt", GROUP)]), "Hikers 2024");
+    assert_eq!(name_of(&[("_chat.txt", CHAT)]), "WhatsApp Chat");
+}
+
+#[test]
+fn prefers_the_name_the_caller_gives() {
+    let export = common::zip_export(&[("WhatsApp Chat with Priya.txt", CHAT.as_bytes())]);
+    let options = ParseOptions::new().chat_name("  Priya Sharma ");
+    let export = WhatsAppChatParser::parse_bytes_with(&export, &options).unwrap();
+    assert_eq!(export.get_chat_name(), "Priya Sharma");
+}
@@ -0,0 +1,41 @@
+#![allow(dead_code)]
+
+use std::io::{Cursor, Write};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// The transcript name WhatsApp gives an export of a chat named `Test`.
+pub const TRANSCRIPT: &str = "WhatsApp Chat with Test.txt";
+
+/// Packs `files` into an in-memory ZIP export, deflating each one.
+pub fn zip_export(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, bytes) in files {
+        writer.start_file(*name, options).expect("start ZIP entry");
+        writer.write_all(bytes).expect("write ZIP entry");
+    }
+    writer.finish().expect("finish ZIP").into_inner()
+}
+
+/// A ZIP export holding only `transcript`.
+pub fn transcript_export(transcript: &str) -> Vec<u8> {
+    zip_export(&[(TRANSCRIPT, transcript.as_bytes())])
+}
+
+/// Rewrites the uncompressed size `zip` declares for `name`, in both its
+/// local header and the central directory, leaving the data as it is.
+pub fn declare_size(zip: &mut [u8], name: &str, size: u32) {
+    let headers = [(0x0403_4b50_u32, 22, 26, 30), (0x0201_4b50, 24, 28, 46)];
+    for (signature, size_at, name_len_at, name_at) in headers {
+        let mut i = 0;
+        while i + name_at <= zip.len() {
+            let word = |at: usize| u32::from_le_bytes(zip[at..at + 4].try_into().unwrap());
+            let name_len = u16::from_le_bytes(zip[i + name_len_at..i + name_len_at + 2].try_into().unwrap()) as usize;
+            if word(i) == signature && zip.get(i + name_at..i + name_at + name_len) == Some(name.as_bytes()) {
+                zip[i + size_at..i + size_at + 4].copy_from_slice(&size.to_le_bytes());
+            }
+            i += 1;
+        }
+    }
+}
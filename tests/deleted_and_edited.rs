@@ -0,0 +1,41 @@
+mod common;
+
+use rust_core::com::zoho::arattai::core::messages::MessageBase;
+use rust_core::com::zoho::arattai::core::messages::whatsapp_message::Content;
+use rust_core::com::zoho::arattai::core::whats_app_parse::WhatsAppChatParser;
+
+const TRANSCRIPT: &str = "\
+12/03/2024, 09:01 - Alice: This message was deleted
+12/03/2024, 09:02 - Bob: You deleted this message
+12/03/2024, 09:03 - Alice: See you at six <This message was edited>
+12/03/2024, 09:04 - Bob: Fine
+";
+
+fn text(content: Option<&Content>) -> (&MessageBase, &str) {
+    match content {
+        Some(Content::Text(t)) => (t.base.as_ref().unwrap(), &t.text),
+        other => panic!("expected a text message, got {:?}", other),
+    }
+}
+
+#[test]
+fn marks_deleted_and_edited_messages() {
+    let export = WhatsAppChatParser::parse_bytes(&common::transcript_export(TRANSCRIPT)).unwrap().to_proto();
+    let messages: Vec<_> = export.messages.iter().map(|m| text(m.content.as_ref())).collect();
+    assert_eq!(messages.len(), 4);
+
+    for (base, body) in &messages[..2] {
+        assert!(base.deleted && !base.edited, "{:?}", base);
+        assert_eq!(*body, "");
+    }
+    assert_eq!(messages[0].0.sender, "Alice");
+    assert_eq!(messages[1].0.sender, "Bob");
+
+    let (base, body) = messages[2];
+    assert!(base.edited && !base.deleted);
+    assert_eq!(body, "See you at six");
+
+    let (base, body) = messages[3];
+    assert!(!base.edited && !base.deleted);
+    assert_eq!(body, "Fine");
+}
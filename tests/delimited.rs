@@ -0,0 +1,125 @@
+mod common;
+
+use prost::Message;
+use rust_core::com::zoho::arattai::core::delimited_export::DelimitedExport;
+use rust_core::com::zoho::arattai::core::group_roster::GroupRoster;
+use rust_core::com::zoho::arattai::core::messages::{DiagnosticKind, ExportFooter, ExportHeader, WhatsAppExport, WhatsAppMessage};
+use rust_core::com::zoho::arattai::core::parse_options::ParseOptions;
+use rust_core::com::zoho::arattai::core::whats_app_parse::WhatsAppChatParser;
+
+/// A group chat with membership changes for the roster and a byte that is
+/// not UTF-8, which the header reports.
+const TRANSCRIPT: &[u8] = b"\
+12/03/2024, 9:00 am - Alice created group \"Trip\"
+12/03/2024, 9:01 am - Alice added Bob
+12/03/2024, 9:02 am - Alice: Plans:
+first line
+12/03/2024, 9:03 am - Bob: caf\xe9
+12/03/2024, 9:04 am - Bob left
+12/03/2024, 9:05 am - Alice: <Media omitted>
+";
+
+/// Streams `export` in chunks of `chunk_size` bytes.
+fn chunks(export: &[u8], chunk_size: usize) -> Vec<Vec<u8>> {
+    let stream = WhatsAppChatParser::messages_from_bytes(export, &ParseOptions::new()).unwrap();
+    let mut delimited = DelimitedExport::new(stream);
+    let mut chunks = Vec::new();
+    loop {
+        let chunk = delimited.next_chunk(chunk_size).unwrap();
+        if chunk.is_empty() {
+            return chunks;
+        }
+        chunks.push(chunk);
+    }
+}
+
+/// Decodes records as they arrive in `pieces`, carrying a record cut off at
+/// the end of one piece over to the next as `decodeChatStream` does, and
+/// rebuilds the export from them with the roster a client would.
+fn decode(pieces: &[Vec<u8>]) -> WhatsAppExport {
+    let mut header = None;
+    let mut messages = Vec::new();
+    let mut messages_ended = false;
+    let mut footer = None;
+    let mut pending = Vec::new();
+    for piece in pieces {
+        pending.extend_from_slice(piece);
+        while let Ok(length) = prost::decode_length_delimiter(pending.as_slice()) {
+            let start = prost::length_delimiter_len(length);
+            if pending.len() < start + length {
+                break;
+            }
+            let record = &pending[start..start + length];
+            assert!(footer.is_none(), "a record after the footer");
+            if header.is_none() {
+                header = Some(ExportHeader::decode(record).unwrap());
+            } else if messages_ended {
+                footer = Some(ExportFooter::decode(record).unwrap());
+            } else if record.is_empty() {
+                messages_ended = true;
+            } else {
+                messages.push(WhatsAppMessage::decode(record).unwrap());
+            }
+            pending.drain(..start + length);
+        }
+    }
+    assert!(pending.is_empty(), "the stream ended inside a record");
+    let mut header = header.expect("a header record");
+    header.diagnostics.extend(footer.expect("a footer record").diagnostics);
+    WhatsAppExport {
+        chat_name: header.chat_name,
+        roster: GroupRoster::reconstruct(&messages).get_periods().to_vec(),
+        messages,
+        date_format: header.date_format,
+        diagnostics: header.diagnostics,
+        time_zone: header.time_zone,
+        locale: header.locale,
+    }
+}
+
+fn parsed(export: &[u8]) -> WhatsAppExport {
+    WhatsAppChatParser::parse_bytes_with(export, &ParseOptions::new()).unwrap().to_proto()
+}
+
+#[test]
+fn records_decode_to_the_parsed_export() {
+    let export = common::zip_export(&[(common::TRANSCRIPT, TRANSCRIPT)]);
+    let expected = parsed(&export);
+    assert_eq!(expected.diagnostics.len(), 1);
+    assert!(!expected.roster.is_empty());
+
+    for chunk_size in [1, 100, 64 * 1024] {
+        assert_eq!(decode(&chunks(&export, chunk_size)), expected, "chunks of {}", chunk_size);
+    }
+}
+
+#[test]
+fn records_split_across_chunks_decode_to_the_parsed_export() {
+    let export = common::zip_export(&[(common::TRANSCRIPT, TRANSCRIPT)]);
+    let stream = chunks(&export, 64 * 1024).concat();
+    for size in [1, 2, 5, 33] {
+        let pieces: Vec<Vec<u8>> = stream.chunks(size).map(<[u8]>::to_vec).collect();
+        assert_eq!(decode(&pieces), parsed(&export), "pieces of {}", size);
+    }
+}
+
+#[test]
+fn diagnostics_raised_while_streaming_come_in_the_footer() {
+    let mut transcript = TRANSCRIPT.to_vec();
+    transcript.extend_from_slice(b"31/02/2024, 9:06 am - Alice: lost in time\n");
+    let export = common::zip_export(&[(common::TRANSCRIPT, &transcript)]);
+    let expected = parsed(&export);
+    let unparsed: Vec<_> = expected
+        .diagnostics
+        .iter()
+        .filter(|d| d.kind == DiagnosticKind::UnparsedTimestamp as i32)
+        .map(|d| d.line)
+        .collect();
+    assert_eq!(unparsed, [8]);
+
+    let chunks = chunks(&export, 64 * 1024);
+    assert_eq!(decode(&chunks), expected);
+    // The header, read before any message, holds only the invalid byte.
+    let header = ExportHeader::decode_length_delimited(chunks[0].as_slice()).unwrap();
+    assert_eq!(header.diagnostics.len(), 1);
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use rust_core::com::zoho::arattai::core::messages::WhatsAppExport;
+use rust_core::interop::{ByteBuffer, free_byte_buffer, parse_chat_with_options_ffi};
+use prost::Message;
+use std::ffi::{CStr, CString};
+
+/// Calls `parse_chat_with_options_ffi` on `path` with default options and
+/// returns the decoded export or the error code and message, freeing the
+/// buffer either way.
+fn parse_ffi(path: &str) -> Result<WhatsAppExport, (u32, String)> {
+    let path = CString::new(path).unwrap();
+    let buffer: ByteBuffer = unsafe { parse_chat_with_options_ffi(path.as_ptr(), std::ptr::null(), 0) };
+    let result = if buffer.error_code == 0 {
+        assert!(buffer.error_message.is_null());
+        let bytes = unsafe { std::slice::from_raw_parts(buffer.data, buffer.len) };
+        Ok(WhatsAppExport::decode(bytes).unwrap())
+    } else {
+        assert!(buffer.data.is_null());
+        assert_eq!(buffer.len, 0);
+        let message = unsafe { CStr::from_ptr(buffer.error_message) }.to_str().unwrap().to_string();
+        Err((buffer.error_code, message))
+    };
+    free_byte_buffer(buffer);
+    result
+}
+
+/// Writes `bytes` to a fresh file under the temporary directory.
+fn temp_file(name: &str, bytes: &[u8]) -> String {
+    let path = std::env::temp_dir().join(format!("rust_core_ffi_{}_{}", std::process::id(), name));
+    std::fs::write(&path, bytes).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn success_has_no_error() {
+    let path = temp_file("chat.zip", &common::transcript_export("12/03/2024, 9:00 am - Alice: hi\n"));
+    let export = parse_ffi(&path).unwrap();
+    assert_eq!(export.messages.len(), 1);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn missing_file_is_an_io_error() {
+    let (code, message) = parse_ffi("/nonexistent/rust_core/chat.zip").unwrap_err();
+    assert_eq!(code, 5);
+    assert!(message.starts_with("I/O error: "), "{}", message);
+}
+
+#[test]
+fn export_without_a_transcript_is_reported() {
+    let path = temp_file("empty.zip", &common::zip_export(&[("IMG-1.jpg", b"\xff\xd8")]));
+    let (code, message) = parse_ffi(&path).unwrap_err();
+    assert_eq!(code, 2);
+    assert_eq!(message, "no chat transcript found in the export");
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn null_path_is_invalid_input() {
+    let buffer = unsafe { parse_chat_with_options_ffi(std::ptr::null(), std::ptr::null(), 0) };
+    assert_eq!(buffer.error_code, 7);
+    let message = unsafe { CStr::from_ptr(buffer.error_message) }.to_str().unwrap().to_string();
+    assert_eq!(message, "invalid input: path is null");
+    free_byte_buffer(buffer);
+}
+
+#[test]
+fn malformed_options_are_invalid_input() {
+    let path = CString::new("/nonexistent/rust_core/chat.zip").unwrap();
+    let options = [0xff_u8];
+    let buffer = unsafe { parse_chat_with_options_ffi(path.as_ptr(), options.as_ptr(), options.len()) };
+    assert_eq!(buffer.error_code, 7);
+    free_byte_buffer(buffer);
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use rust_core::com::zoho::arattai::core::messages::whatsapp_message::Content;
+use rust_core::com::zoho::arattai::core::whats_app_export::WhatsAppExport;
+use rust_core::com::zoho::arattai::core::whats_app_parse::WhatsAppChatParser;
+
+const TRANSCRIPT: &str = "\
+12/03/2024, 09:01 - Alice: IMG-20240312-WA0001.jpg (file attached)
+12/03/2024, 09:02 - Bob: Plan.pdf (file attached)
+";
+
+const IMAGE: &[u8] = b"not really a jpeg";
+const PLAN: &[u8] = b"%PDF-1.4";
+
+/// An export unpacked into a folder of its own, with the attachments one
+/// level further down.
+const FILES: [(&str, &[u8]); 3] = [
+    ("Export/WhatsApp Chat with Dana.txt", TRANSCRIPT.as_bytes()),
+    ("Export/Media/IMG-20240312-WA0001.jpg", IMAGE),
+    ("Export/Media/Documents/Plan.pdf", PLAN),
+];
+
+fn assert_attachments_found(export: &WhatsAppExport) {
+    assert_eq!(export.get_chat_name(), "Dana");
+    let messages = export.get_all_messages();
+    assert_eq!(messages.len(), 2);
+    match messages[0].content.as_ref() {
+        Some(Content::Image(image)) => {
+            assert_eq!(image.name, "Export/Media/IMG-20240312-WA0001.jpg");
+            assert_eq!(image.size, IMAGE.len() as u64);
+        }
+        other => panic!("expected an image, got {:?}", other),
+    }
+    match messages[1].content.as_ref() {
+        Some(Content::Document(document)) => {
+            assert_eq!(document.name, "Export/Media/Documents/Plan.pdf");
+            assert_eq!(document.size, PLAN.len() as u64);
+        }
+        other => panic!("expected a document, got {:?}", other),
+    }
+}
+
+#[test]
+fn finds_files_in_nested_folders_of_an_archive() {
+    let export = WhatsAppChatParser::parse_bytes(&common::zip_export(&FILES)).unwrap();
+    assert_attachments_found(&export);
+}
+
+#[test]
+fn reads_an_unpacked_export_folder() {
+    let root = tempfile::tempdir().unwrap();
+    for (name, bytes) in FILES {
+        let path = root.path().join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, bytes).unwrap();
+    }
+    let export = WhatsAppChatParser::parse(root.path()).unwrap();
+    assert_attachments_found(&export);
+
+    // The export's own folder works as well as the one above it.
+    let export = WhatsAppChatParser::parse(root.path().join("Export")).unwrap();
+    assert_eq!(export.get_chat_name(), "Dana");
+    match export.get_all_messages()[0].content.as_ref() {
+        Some(Content::Image(image)) => assert_eq!(image.name, "Media/IMG-20240312-WA0001.jpg"),
+        other => panic!("expected an image, got {:?}", other),
+    }
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use rust_core::com::zoho::arattai::core::messages::whatsapp_message::Content;
+use rust_core::com::zoho::arattai::core::messages::{SystemEventKind, TimestampStatus};
+use rust_core::com::zoho::arattai::core::whats_app_parse::WhatsAppChatParser;
+
+/// An iOS export: bracketed timestamps with seconds, the transcript named
+/// `_chat.txt` and system lines marked with U+200E under the chat name.
+const TRANSCRIPT: &str = "\
+[12/03/24, 09:01:33] Trip: \u{200e}Messages and calls are end-to-end encrypted.
+[12/03/24, 09:02:05] Alice: Morning
+[12/03/24, 21:15:00] Bob: Evening: late
+still Bob
+[12/03/24, 21:16:00] Bob: Night
+";
+
+#[test]
+fn reads_an_ios_transcript() {
+    let export = common::zip_export(&[("_chat.txt", TRANSCRIPT.as_bytes())]);
+    let export = WhatsAppChatParser::parse_bytes(&export).unwrap().to_proto();
+    assert_eq!(export.date_format, "dd/mm/yy, HH:mm");
+    assert_eq!(export.messages.len(), 4);
+
+    match export.messages[0].content.as_ref() {
+        Some(Content::System(m)) => assert_eq!(m.kind, SystemEventKind::EncryptionNotice as i32),
+        other => panic!("expected a system message, got {:?}", other),
+    }
+    let texts: Vec<_> = export.messages[1..]
+        .iter()
+        .map(|m| match m.content.as_ref() {
+            Some(Content::Text(t)) => t,
+            other => panic!("expected a text message, got {:?}", other),
+        })
+        .collect();
+
+    let alice = texts[0].base.as_ref().unwrap();
+    assert_eq!(alice.sender, "Alice");
+    assert_eq!(texts[0].text, "Morning");
+    assert_eq!(alice.raw_timestamp, "12/03/24, 09:02:05");
+    assert_eq!(alice.timestamp_status, TimestampStatus::Exact as i32);
+    // 2024-03-12 09:02:05 UTC.
+    assert_eq!(alice.timestamp.as_ref().unwrap().seconds, 1_710_234_125);
+
+    assert_eq!(texts[1].base.as_ref().unwrap().sender, "Bob");
+    assert_eq!(texts[1].text, "Evening: late\nstill Bob");
+    assert_eq!(texts[1].base.as_ref().unwrap().timestamp.as_ref().unwrap().seconds, 1_710_278_100);
+}
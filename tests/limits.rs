@@ -0,0 +1,51 @@
+mod common;
+
+use rust_core::com::zoho::arattai::core::parse_error::ParseError;
+use rust_core::com::zoho::arattai::core::parse_options::ParseOptions;
+use rust_core::com::zoho::arattai::core::whats_app_parse::WhatsAppChatParser;
+
+/// A transcript of `count` messages from Alice and Bob.
+fn transcript(count: usize) -> String {
+    (0..count)
+        .map(|i| format!("12/03/2024, 9:{:02} am - {}: message {}\n", i % 60, ["Alice", "Bob"][i % 2], i))
+        .collect()
+}
+
+fn parse(bytes: &[u8], options: &ParseOptions) -> Result<usize, ParseError> {
+    WhatsAppChatParser::parse_bytes_with(bytes, options).map(|export| export.get_all_messages().len())
+}
+
+#[test]
+fn declared_transcript_size_is_checked() {
+    let export = common::transcript_export(&transcript(10));
+    let options = ParseOptions::new().max_transcript_size(100);
+    assert!(matches!(parse(&export, &options), Err(ParseError::LimitExceeded(_))));
+    assert_eq!(parse(&export, &ParseOptions::new().max_transcript_size(10_000)).unwrap(), 10);
+}
+
+#[test]
+fn declared_export_size_is_checked() {
+    let export = common::zip_export(&[(common::TRANSCRIPT, transcript(10).as_bytes()), ("notes.pdf", &[0; 4096])]);
+    let options = ParseOptions::new().max_export_size(4096);
+    assert!(matches!(parse(&export, &options), Err(ParseError::LimitExceeded(_))));
+}
+
+#[test]
+fn understated_transcript_is_not_read_past_its_declared_size() {
+    let text = transcript(100);
+    let mut export = common::transcript_export(&text);
+    common::declare_size(&mut export, common::TRANSCRIPT, 200);
+
+    let options = ParseOptions::new().max_transcript_size(1000);
+    assert!(text.len() > 1000);
+    let error = parse(&export, &options).unwrap_err();
+    assert!(matches!(error, ParseError::TranscriptUnreadable(_)), "{}", error);
+    assert!(error.to_string().contains("more than the 200 bytes the archive declares"), "{}", error);
+}
+
+#[test]
+fn message_limit_is_checked() {
+    let export = common::transcript_export(&transcript(10));
+    assert!(matches!(parse(&export, &ParseOptions::new().max_messages(9)), Err(ParseError::LimitExceeded(_))));
+    assert_eq!(parse(&export, &ParseOptions::new().max_messages(10)).unwrap(), 10);
+}
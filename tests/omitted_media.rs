@@ -0,0 +1,64 @@
+mod common;
+
+use rust_core::com::zoho::arattai::core::messages::whatsapp_message::Content;
+use rust_core::com::zoho::arattai::core::messages::{MessageBase, Type, WhatsAppMessage};
+use rust_core::com::zoho::arattai::core::whats_app_parse::WhatsAppChatParser;
+
+fn base(message: &WhatsAppMessage) -> &MessageBase {
+    match message.content.as_ref() {
+        Some(Content::OmittedMedia(m)) => m.base.as_ref(),
+        Some(Content::Image(m)) => m.base.as_ref(),
+        Some(Content::Video(m)) => m.base.as_ref(),
+        Some(Content::Audio(m)) => m.base.as_ref(),
+        Some(Content::Document(m)) => m.base.as_ref(),
+        Some(Content::Sticker(m)) => m.base.as_ref(),
+        Some(Content::Text(m)) => m.base.as_ref(),
+        other => panic!("unexpected message {:?}", other),
+    }
+    .unwrap()
+}
+
+#[test]
+fn marks_android_placeholders_as_omitted_media() {
+    let transcript = "\
+12/03/2024, 09:01 - Alice: <Media omitted>
+12/03/2024, 09:02 - Bob: Media omitted, sadly
+";
+    let export = WhatsAppChatParser::parse_bytes(&common::transcript_export(transcript)).unwrap().to_proto();
+    assert!(matches!(export.messages[0].content, Some(Content::OmittedMedia(_))));
+    assert!(base(&export.messages[0]).media_omitted);
+    assert_eq!(base(&export.messages[0]).r#type, Type::MediaOmitted as i32);
+    assert!(matches!(export.messages[1].content, Some(Content::Text(_))));
+    assert!(!base(&export.messages[1]).media_omitted);
+}
+
+#[test]
+fn keeps_the_kind_of_ios_placeholders() {
+    let transcript = "\
+[12/03/24, 09:01:00] Alice: \u{200e}image omitted
+[12/03/24, 09:02:00] Bob: \u{200e}video omitted
+[12/03/24, 09:03:00] Alice: \u{200e}audio omitted
+[12/03/24, 09:04:00] Bob: Plan.pdf \u{200e}document omitted
+[12/03/24, 09:05:00] Alice: \u{200e}sticker omitted
+[12/03/24, 09:06:00] Bob: Plan.pdf document omitted
+";
+    let export = common::zip_export(&[("_chat.txt", transcript.as_bytes())]);
+    let export = WhatsAppChatParser::parse_bytes(&export).unwrap().to_proto();
+    assert_eq!(export.messages.len(), 6);
+
+    for message in &export.messages[..5] {
+        assert!(base(message).media_omitted, "{:?}", message);
+    }
+    match export.messages[0].content.as_ref() {
+        // An absent attachment gets no made-up file name.
+        Some(Content::Image(image)) => assert_eq!(image.name, ""),
+        other => panic!("expected an image, got {:?}", other),
+    }
+    assert!(matches!(export.messages[1].content, Some(Content::Video(_))));
+    assert!(matches!(export.messages[2].content, Some(Content::Audio(_))));
+    assert!(matches!(export.messages[3].content, Some(Content::Document(_))));
+    assert!(matches!(export.messages[4].content, Some(Content::Sticker(_))));
+    // Without the mark, a file name and the words are an ordinary message.
+    assert!(matches!(export.messages[5].content, Some(Content::Text(_))));
+    assert!(!base(&export.messages[5]).media_omitted);
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use rust_core::com::zoho::arattai::core::messages::whatsapp_message::Content;
+use rust_core::com::zoho::arattai::core::messages::{DiagnosticKind, TimestampStatus, WhatsAppMessage};
+use rust_core::com::zoho::arattai::core::parse_error::ParseError;
+use rust_core::com::zoho::arattai::core::parse_options::ParseOptions;
+use rust_core::com::zoho::arattai::core::whats_app_parse::WhatsAppChatParser;
+use std::io::{Cursor, Write};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// A group chat with a system event, a continuation line, an attachment
+/// that is read while the transcript is open, an unreadable timestamp and
+/// an omitted medium.
+const TRANSCRIPT: &str = "\
+12/03/2024, 9:00 am - Alice created group \"Trip\"
+12/03/2024, 9:01 am - Alice added Bob
+12/03/2024, 9:02 am - Alice: Plans:
+first line
+second line
+12/03/2024, 9:03 am - Bob: Carol.vcf (file attached)
+31/02/2024, 9:04 am - Bob: lost in time
+12/03/2024, 9:05 am - Alice: <Media omitted>
+12/03/2024, 9:06 am - Bob: bye
+";
+
+const CARD: &[u8] = b"BEGIN:VCARD\r\nVERSION:3.0\r\nFN:Carol\r\nTEL;TYPE=CELL:+1 555 0100\r\nEND:VCARD\r\n";
+
+fn export() -> Vec<u8> {
+    common::zip_export(&[(common::TRANSCRIPT, TRANSCRIPT.as_bytes()), ("Carol.vcf", CARD)])
+}
+
+/// The sender, what the message says, its time in seconds and how sure
+/// that time is.
+fn summary(message: &WhatsAppMessage) -> (String, String, i64, i32) {
+    let base = message.base().unwrap();
+    let text = match message.content.as_ref() {
+        Some(Content::System(m)) => m.text.clone(),
+        Some(Content::Text(m)) => m.text.clone(),
+        Some(Content::Contact(m)) => format!("{}: {}", m.name, m.contacts[0].formatted_name),
+        Some(Content::OmittedMedia(_)) => "omitted".to_string(),
+        other => panic!("unexpected message {:?}", other),
+    };
+    (base.sender.clone(), text, base.timestamp.as_ref().unwrap().seconds, base.timestamp_status)
+}
+
+/// The summaries of the messages in `TRANSCRIPT`.
+fn expected() -> Vec<(String, String, i64, i32)> {
+    // 2024-03-12 09:00 UTC.
+    let at = |minute: i64| 1_710_234_000 + minute * 60;
+    let exact = TimestampStatus::Exact as i32;
+    let expected = [
+        ("", "Alice created group \"Trip\"", at(0), exact),
+        ("", "Alice added Bob", at(1), exact),
+        ("Alice", "Plans:\nfirst line\nsecond line", at(2), exact),
+        ("Bob", "Carol.vcf: Carol", at(3), exact),
+        // Halfway between the messages either side.
+        ("Bob", "lost in time", at(4), TimestampStatus::Approximate as i32),
+        ("Alice", "omitted", at(5), exact),
+        ("Bob", "bye", at(6), exact),
+    ];
+    expected.iter().map(|(sender, text, at, status)| (sender.to_string(), text.to_string(), *at, *status)).collect()
+}
+
+#[test]
+fn stream_reads_every_message() {
+    let bytes = export();
+    let mut stream = WhatsAppChatParser::messages_from_bytes(&bytes, &ParseOptions::new()).unwrap();
+    assert_eq!(stream.get_chat_name(), "Test");
+    let streamed: Vec<WhatsAppMessage> = stream.by_ref().collect::<Result<_, _>>().unwrap();
+
+    assert_eq!(streamed.iter().map(summary).collect::<Vec<_>>(), expected());
+
+    let diagnostics = stream.get_diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!((diagnostics[0].line, diagnostics[0].kind), (7, DiagnosticKind::UnparsedTimestamp as i32));
+}
+
+#[test]
+fn stream_matches_parse_from_a_file() {
+    let path = std::env::temp_dir().join(format!("rust_core_streaming_{}.zip", std::process::id()));
+    std::fs::write(&path, export()).unwrap();
+    let options = ParseOptions::new();
+    let parsed = WhatsAppChatParser::parse_with(&path, &options).unwrap();
+    let streamed: Vec<WhatsAppMessage> =
+        WhatsAppChatParser::messages(&path, &options).unwrap().collect::<Result<_, _>>().unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(streamed.iter().map(summary).collect::<Vec<_>>(), expected());
+    assert_eq!(streamed, parsed.get_all_messages());
+}
+
+#[test]
+fn corrupt_transcript_fails_its_checksum() {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    writer.start_file(common::TRANSCRIPT, stored).unwrap();
+    writer.write_all(TRANSCRIPT.as_bytes()).unwrap();
+    let mut bytes = writer.finish().unwrap().into_inner();
+    let at = bytes.windows(3).position(|w| w == b"bye").unwrap();
+    bytes[at] = b'B';
+
+    let error = WhatsAppChatParser::parse_bytes(&bytes).unwrap_err();
+    assert!(matches!(error, ParseError::TranscriptUnreadable(_)), "{}", error);
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use rust_core::com::zoho::arattai::core::messages::whatsapp_message::Content;
+use rust_core::com::zoho::arattai::core::messages::{SystemEventKind, WhatsAppExport};
+use rust_core::com::zoho::arattai::core::whats_app_parse::WhatsAppChatParser;
+
+/// Parses a group chat between Alice and Bob with `line` from a one-time
+/// sender at 9:10 in the middle.
+fn parse_with_line(line: &str) -> WhatsAppExport {
+    let transcript = format!(
+        "12/03/2024, 9:00 am - Alice: hi\n\
+         12/03/2024, 9:05 am - Bob: hello\n\
+         12/03/2024, 9:10 am - {}\n\
+         12/03/2024, 9:15 am - Alice: bye\n\
+         12/03/2024, 9:20 am - Bob: bye\n",
+        line
+    );
+    WhatsAppChatParser::parse_bytes(&common::transcript_export(&transcript)).unwrap().to_proto()
+}
+
+fn assert_text(export: &WhatsAppExport, sender: &str, text: &str) {
+    match export.messages[2].content.as_ref() {
+        Some(Content::Text(m)) => {
+            assert_eq!(m.base.as_ref().unwrap().sender, sender);
+            assert_eq!(m.text, text);
+        }
+        other => panic!("expected a text message, got {:?}", other),
+    }
+}
+
+#[test]
+fn one_time_sender_mentioning_added_is_a_message() {
+    let export = parse_with_line("Carol: I just added you to my contacts");
+    assert_text(&export, "Carol", "I just added you to my contacts");
+}
+
+#[test]
+fn one_time_sender_mentioning_left_is_a_message() {
+    let export = parse_with_line("Dave: Sorry I left");
+    assert_text(&export, "Dave", "Sorry I left");
+}
+
+#[test]
+fn one_time_sender_mentioning_removed_is_a_message() {
+    let export = parse_with_line("Erin: Alice removed Bob");
+    assert_text(&export, "Erin", "Alice removed Bob");
+}
+
+#[test]
+fn subject_with_a_colon_is_still_a_system_event() {
+    let export = parse_with_line("Alice changed the subject from \"Plans\" to \"Trip: June\"");
+    match export.messages[2].content.as_ref() {
+        Some(Content::System(m)) => assert_eq!(m.kind, SystemEventKind::SubjectChanged as i32),
+        other => panic!("expected a system message, got {:?}", other),
+    }
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use rust_core::com::zoho::arattai::core::messages::{DiagnosticKind, TimestampStatus};
+use rust_core::com::zoho::arattai::core::parse_options::ParseOptions;
+use rust_core::com::zoho::arattai::core::whats_app_parse::WhatsAppChatParser;
+
+/// 31 February cannot be read, so the second message has no exact time.
+const TRANSCRIPT: &str = "\
+12/03/2024, 9:00 am - Alice: one
+31/02/2024, 9:04 am - Bob: two
+12/03/2024, 9:10 am - Alice: three
+";
+
+#[test]
+fn unreadable_timestamp_is_interpolated_by_default() {
+    let export = WhatsAppChatParser::parse_bytes(&common::transcript_export(TRANSCRIPT)).unwrap();
+    let bases: Vec<_> = export.get_all_messages().iter().map(|m| m.base().unwrap()).collect();
+
+    assert_eq!(bases[1].timestamp_status, TimestampStatus::Approximate as i32);
+    assert_eq!(bases[1].raw_timestamp, "31/02/2024, 9:04 am");
+    let seconds = |i: usize| bases[i].timestamp.as_ref().unwrap().seconds;
+    assert_eq!(seconds(1), (seconds(0) + seconds(2)) / 2);
+    assert_eq!(bases[0].timestamp_status, TimestampStatus::Exact as i32);
+    assert_eq!(bases[2].timestamp_status, TimestampStatus::Exact as i32);
+
+    let diagnostics = export.get_diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].line, 2);
+    assert_eq!(diagnostics[0].kind, DiagnosticKind::UnparsedTimestamp as i32);
+}
+
+#[test]
+fn unreadable_timestamps_at_the_end_copy_the_last_time() {
+    let transcript = "12/03/2024, 9:00 am - Alice: one\n31/02/2024, 9:04 am - Bob: two\n";
+    let export = WhatsAppChatParser::parse_bytes(&common::transcript_export(transcript)).unwrap();
+    let bases: Vec<_> = export.get_all_messages().iter().map(|m| m.base().unwrap()).collect();
+
+    assert_eq!(bases[1].timestamp_status, TimestampStatus::Approximate as i32);
+    assert_eq!(bases[1].timestamp, bases[0].timestamp);
+}
+
+#[test]
+fn interpolation_can_be_turned_off() {
+    let options = ParseOptions::new().interpolate_timestamps(false);
+    let export = WhatsAppChatParser::parse_bytes_with(&common::transcript_export(TRANSCRIPT), &options).unwrap();
+    let bases: Vec<_> = export.get_all_messages().iter().map(|m| m.base().unwrap()).collect();
+
+    assert_eq!(bases.len(), 3);
+    assert_eq!(bases[1].timestamp_status, TimestampStatus::Unknown as i32);
+    assert_eq!(bases[1].timestamp, None);
+    assert_eq!(bases[1].raw_timestamp, "31/02/2024, 9:04 am");
+    assert_eq!(export.get_diagnostics().len(), 1);
+}
+
+#[test]
+fn long_runs_of_unreadable_timestamps_are_not_held_back() {
+    // More messages than the stream holds back at once.
+    let undated = 1001;
+    let mut transcript = String::from("12/03/2024, 9:00 am - Alice: one\n");
+    for i in 0..undated {
+        transcript.push_str(&format!("31/02/2024, 9:01 am - {}: lost {}\n", ["Bob", "Alice"][i % 2], i));
+    }
+    transcript.push_str("12/03/2024, 9:10 am - Bob: found\n");
+    let export = WhatsAppChatParser::parse_bytes(&common::transcript_export(&transcript)).unwrap();
+    let bases: Vec<_> = export.get_all_messages().iter().map(|m| m.base().unwrap()).collect();
+
+    assert_eq!(bases.len(), undated + 2);
+    assert!(bases[1..=undated].iter().all(|base| base.timestamp_status == TimestampStatus::Unknown as i32));
+    assert!(bases[1..=undated].iter().all(|base| base.timestamp.is_none()));
+    assert_eq!(bases[undated + 1].timestamp_status, TimestampStatus::Exact as i32);
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use rust_core::com::zoho::arattai::core::messages::whatsapp_message::Content;
+use rust_core::com::zoho::arattai::core::parse_error::ParseError;
+use rust_core::com::zoho::arattai::core::parse_options::ParseOptions;
+use rust_core::com::zoho::arattai::core::whats_app_parse::WhatsAppChatParser;
+
+const NOTES: &[u8] = b"12/03/2024, 08:00 - Someone: drafted elsewhere\n";
+
+const CHAT: &str = "\
+12/03/2024, 09:01 - Alice: notes.txt (file attached)
+12/03/2024, 09:02 - Bob: thanks
+";
+
+#[test]
+fn prefers_the_whatsapp_transcript_over_other_text_files() {
+    let export = common::zip_export(&[
+        ("notes.txt", NOTES),
+        ("WhatsApp Chat with Bob.txt", CHAT.as_bytes()),
+        ("readme.txt", b"nothing here\n"),
+    ]);
+    let stream = WhatsAppChatParser::messages_from_bytes(&export, &ParseOptions::new()).unwrap();
+    assert_eq!(stream.get_transcript_name(), "WhatsApp Chat with Bob.txt");
+
+    let export = WhatsAppChatParser::parse_bytes(&export).unwrap();
+    assert_eq!(export.get_chat_name(), "Bob");
+    let messages = export.get_all_messages();
+    assert_eq!(messages.len(), 2);
+    // The other text files stay in the export as attachments.
+    match messages[0].content.as_ref() {
+        Some(Content::Document(document)) => {
+            assert_eq!(document.name, "notes.txt");
+            assert_eq!(document.size, NOTES.len() as u64);
+        }
+        other => panic!("expected a document, got {:?}", other),
+    }
+}
+
+#[test]
+fn skips_a_likely_name_without_message_headers() {
+    let export = common::zip_export(&[
+        ("_chat.txt", b"This file was left empty.\n"),
+        ("backup/export.txt", CHAT.as_bytes()),
+    ]);
+    let stream = WhatsAppChatParser::messages_from_bytes(&export, &ParseOptions::new()).unwrap();
+    assert_eq!(stream.get_transcript_name(), "backup/export.txt");
+    assert_eq!(WhatsAppChatParser::parse_bytes(&export).unwrap().get_all_messages().len(), 2);
+
+    // Without any headers, a WhatsApp name is still taken as the transcript
+    // and then rejected for its content; other names are not taken at all.
+    let export = common::zip_export(&[("_chat.txt", b"nothing\n"), ("notes.txt", b"nothing\n")]);
+    assert!(matches!(WhatsAppChatParser::parse_bytes(&export), Err(ParseError::UnsupportedFormat)));
+    let export = common::zip_export(&[("notes.txt", b"nothing\n")]);
+    assert!(matches!(WhatsAppChatParser::parse_bytes(&export), Err(ParseError::NoTranscript)));
+}